@@ -0,0 +1,117 @@
+//! The SQLite database of the kitties, owners, listings and sales.
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use node_template_runtime::{AccountId, Balance, BlockNumber};
+use crate::Result;
+
+// The balances are stored as text, as they may not fit in an SQLite integer.
+const SCHEMA: &str = "
+	CREATE TABLE IF NOT EXISTS kitties (
+		id INTEGER PRIMARY KEY,
+		dna TEXT NOT NULL,
+		owner TEXT NOT NULL,
+		parent1 INTEGER,
+		parent2 INTEGER,
+		block INTEGER
+	);
+	CREATE INDEX IF NOT EXISTS kitties_owner ON kitties (owner);
+	CREATE TABLE IF NOT EXISTS listings (
+		kitty_id INTEGER PRIMARY KEY,
+		price TEXT NOT NULL
+	);
+	CREATE TABLE IF NOT EXISTS sales (
+		id INTEGER PRIMARY KEY AUTOINCREMENT,
+		kitty_id INTEGER NOT NULL,
+		seller TEXT NOT NULL,
+		buyer TEXT NOT NULL,
+		price TEXT NOT NULL,
+		block INTEGER NOT NULL
+	);
+	CREATE INDEX IF NOT EXISTS sales_kitty_id ON sales (kitty_id);
+	CREATE TABLE IF NOT EXISTS meta (
+		key TEXT PRIMARY KEY,
+		value INTEGER NOT NULL
+	);
+";
+
+pub struct Db {
+	conn: Connection,
+}
+
+impl Db {
+	pub fn open(path: &str) -> Result<Self> {
+		let conn = Connection::open(path)?;
+		conn.execute_batch(SCHEMA)?;
+		Ok(Db { conn })
+	}
+
+	/// The last finalized block which was indexed.
+	pub fn last_block(&self) -> Result<Option<BlockNumber>> {
+		Ok(self.conn
+			.query_row("SELECT value FROM meta WHERE key = 'last_block'", [], |row| row.get(0))
+			.optional()?)
+	}
+
+	/// Start a transaction, which is committed with the last indexed block.
+	pub fn transaction(&mut self) -> Result<Transaction> {
+		Ok(self.conn.transaction()?)
+	}
+}
+
+pub fn set_last_block(tx: &Transaction, number: BlockNumber) -> Result<()> {
+	tx.execute("INSERT OR REPLACE INTO meta (key, value) VALUES ('last_block', ?1)", params![number])?;
+	Ok(())
+}
+
+pub fn insert_kitty(
+	tx: &Transaction,
+	kitty_id: u32,
+	dna: &[u8; 16],
+	owner: &AccountId,
+	parents: Option<(u32, u32)>,
+	block: Option<BlockNumber>,
+) -> Result<()> {
+	tx.execute(
+		"INSERT OR REPLACE INTO kitties (id, dna, owner, parent1, parent2, block) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+		params![kitty_id, hex::encode(dna), owner.to_string(), parents.map(|p| p.0), parents.map(|p| p.1), block],
+	)?;
+	Ok(())
+}
+
+pub fn set_owner(tx: &Transaction, kitty_id: u32, owner: &AccountId) -> Result<()> {
+	tx.execute("UPDATE kitties SET owner = ?2 WHERE id = ?1", params![kitty_id, owner.to_string()])?;
+	Ok(())
+}
+
+pub fn remove_kitty(tx: &Transaction, kitty_id: u32) -> Result<()> {
+	tx.execute("DELETE FROM kitties WHERE id = ?1", params![kitty_id])?;
+	remove_listing(tx, kitty_id)
+}
+
+pub fn set_listing(tx: &Transaction, kitty_id: u32, price: Balance) -> Result<()> {
+	tx.execute(
+		"INSERT OR REPLACE INTO listings (kitty_id, price) VALUES (?1, ?2)",
+		params![kitty_id, price.to_string()],
+	)?;
+	Ok(())
+}
+
+pub fn remove_listing(tx: &Transaction, kitty_id: u32) -> Result<()> {
+	tx.execute("DELETE FROM listings WHERE kitty_id = ?1", params![kitty_id])?;
+	Ok(())
+}
+
+pub fn insert_sale(
+	tx: &Transaction,
+	kitty_id: u32,
+	seller: &AccountId,
+	buyer: &AccountId,
+	price: Balance,
+	block: BlockNumber,
+) -> Result<()> {
+	tx.execute(
+		"INSERT INTO sales (kitty_id, seller, buyer, price, block) VALUES (?1, ?2, ?3, ?4, ?5)",
+		params![kitty_id, seller.to_string(), buyer.to_string(), price.to_string(), block],
+	)?;
+	Ok(())
+}
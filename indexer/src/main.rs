@@ -0,0 +1,304 @@
+//! Kitties indexer.
+//!
+//! Follow the finalized blocks of a local node over RPC, and mirror the kitties, owners,
+//! listings and sales of `pallet_kitties` into an SQLite database. Only the finalized blocks are
+//! indexed, so the database never needs to roll back a reorg.
+//!
+//! An empty database starts from a snapshot of the storage at the finalized head, then the
+//! events of each following block are applied. The events are decoded with the types of
+//! `node-template-runtime`, so rebuild the indexer with the runtime after a runtime upgrade.
+
+mod db;
+mod rpc;
+
+use std::{thread, time::Duration};
+use codec::Decode;
+use structopt::StructOpt;
+use sp_core::hashing::twox_128;
+use frame_system::EventRecord;
+use node_template_runtime::{AccountId, Balance, BlockNumber, Event, Hash, Runtime};
+use pallet_kitties::{Event as KittyEvent, Kitty, SaleRecord};
+use rusqlite::Transaction;
+use rpc::Chain;
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The name of pallet_kitties in `construct_runtime!`, the prefix of its storage.
+const PALLET_PREFIX: &[u8] = b"KittiesModule";
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "kitties-indexer", about = "Mirror the kitties of a node into SQLite.")]
+struct Opt {
+	/// The HTTP RPC endpoint of the node.
+	#[structopt(long, default_value = "http://127.0.0.1:9933")]
+	url: String,
+
+	/// The path of the SQLite database.
+	#[structopt(long, default_value = "kitties.db")]
+	database: String,
+
+	/// The interval of polling the finalized head, in milliseconds.
+	#[structopt(long, default_value = "6000")]
+	interval: u64,
+}
+
+fn main() -> Result<()> {
+	env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+	let opt = Opt::from_args();
+	let client = rpc::Client::new(&opt.url);
+	let mut db = db::Db::open(&opt.database)?;
+
+	loop {
+		if let Err(e) = sync(&client, &mut db) {
+			log::warn!("Sync failed, retrying: {}", e);
+		}
+		thread::sleep(Duration::from_millis(opt.interval));
+	}
+}
+
+// Index the blocks up to the finalized head, one transaction per block.
+fn sync(client: &impl Chain, db: &mut db::Db) -> Result<()> {
+	let (head_hash, head) = client.finalized_head()?;
+	let last = match db.last_block()? {
+		Some(last) => last,
+		None => return snapshot(client, db, head_hash, head),
+	};
+
+	for number in last + 1..=head {
+		let hash = client.block_hash(number)?;
+		let events: Vec<EventRecord<Event, Hash>> = client
+			.storage(&storage_prefix(b"System", b"Events"), hash)?
+			.unwrap_or_default();
+
+		let tx = db.transaction()?;
+		for record in events {
+			if let Event::KittiesModule(event) = record.event {
+				apply_event(&tx, event, number)?;
+			}
+		}
+		db::set_last_block(&tx, number)?;
+		tx.commit()?;
+		log::info!("Indexed block #{}", number);
+	}
+
+	Ok(())
+}
+
+// Copy the storage of pallet_kitties at the block `at`.
+fn snapshot(client: &impl Chain, db: &mut db::Db, at: Hash, number: BlockNumber) -> Result<()> {
+	log::info!("Taking a snapshot at block #{}", number);
+	let tx = db.transaction()?;
+
+	for (kitty_id, kitty) in storage_map::<Kitty>(client, b"Kitties", at)? {
+		let owner: AccountId = client
+			.storage(&[storage_prefix(PALLET_PREFIX, b"Owner"), map_key(kitty_id)].concat(), at)?
+			.ok_or("A kitty has no owner")?;
+		db::insert_kitty(&tx, kitty_id, &kitty.0, &owner, None, None)?;
+	}
+	for (kitty_id, price) in storage_map::<Balance>(client, b"ListForSale", at)? {
+		db::set_listing(&tx, kitty_id, price)?;
+	}
+	for (kitty_id, sales) in storage_map::<Vec<SaleRecord<AccountId, Balance, BlockNumber>>>(client, b"KittySales", at)? {
+		for sale in sales {
+			db::insert_sale(&tx, kitty_id, &sale.seller, &sale.buyer, sale.price, sale.block)?;
+		}
+	}
+
+	db::set_last_block(&tx, number)?;
+	tx.commit()?;
+	Ok(())
+}
+
+fn apply_event(tx: &Transaction, event: KittyEvent<Runtime>, block: BlockNumber) -> Result<()> {
+	match event {
+		KittyEvent::KittyCreated { owner, kitty_id, dna } =>
+			db::insert_kitty(tx, kitty_id, &dna, &owner, None, Some(block)),
+		KittyEvent::KittyBred { owner, child, parent1, parent2, dna } =>
+			db::insert_kitty(tx, child, &dna, &owner, Some((parent1, parent2)), Some(block)),
+		KittyEvent::KittyTransferred(_, to, kitty_id) => db::set_owner(tx, kitty_id, &to),
+		KittyEvent::KittyListed(_, kitty_id, price) => db::set_listing(tx, kitty_id, price),
+		KittyEvent::KittyDelisted { kitty_id, .. } => db::remove_listing(tx, kitty_id),
+		KittyEvent::KittySold(seller, buyer, kitty_id, price) => {
+			db::set_owner(tx, kitty_id, &buyer)?;
+			db::insert_sale(tx, kitty_id, &seller, &buyer, price, block)
+		},
+		KittyEvent::KittyReleased(_, kitty_id) | KittyEvent::KittyBurned(_, kitty_id) =>
+			db::remove_kitty(tx, kitty_id),
+		_ => Ok(()),
+	}
+}
+
+// All the entries of a `Blake2_128Concat` map of pallet_kitties keyed by the kitty index.
+fn storage_map<V: Decode>(client: &impl Chain, item: &[u8], at: Hash) -> Result<Vec<(u32, V)>> {
+	let prefix = storage_prefix(PALLET_PREFIX, item);
+	let mut entries = Vec::new();
+	for key in client.storage_keys(&prefix, at)? {
+		// Skip the prefix and the blake2_128 hash of the key.
+		let kitty_id = u32::decode(&mut &key[prefix.len() + 16..])?;
+		if let Some(value) = client.storage(&key, at)? {
+			entries.push((kitty_id, value));
+		}
+	}
+	Ok(entries)
+}
+
+fn storage_prefix(pallet: &[u8], item: &[u8]) -> Vec<u8> {
+	[twox_128(pallet), twox_128(item)].concat()
+}
+
+fn map_key(kitty_id: u32) -> Vec<u8> {
+	let encoded = codec::Encode::encode(&kitty_id);
+	[&sp_core::hashing::blake2_128(&encoded)[..], &encoded[..]].concat()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{cell::RefCell, collections::BTreeMap};
+	use codec::Encode;
+	use frame_system::Phase;
+
+	// A finalized chain in memory: the hash and the storage of each block.
+	#[derive(Default)]
+	struct TestChain {
+		blocks: Vec<(Hash, BTreeMap<Vec<u8>, Vec<u8>>)>,
+		fetched: RefCell<Vec<BlockNumber>>,
+	}
+
+	impl TestChain {
+		// Finalize a block with the storage of the last one and `events`.
+		fn push_block(&mut self, events: Vec<KittyEvent<Runtime>>) {
+			let mut storage = self.blocks.last().map(|b| b.1.clone()).unwrap_or_default();
+			let records: Vec<EventRecord<Event, Hash>> = events
+				.into_iter()
+				.map(|event| EventRecord { phase: Phase::ApplyExtrinsic(0), event: Event::KittiesModule(event), topics: vec![] })
+				.collect();
+			storage.insert(storage_prefix(b"System", b"Events"), records.encode());
+			self.blocks.push((Hash::repeat_byte(self.blocks.len() as u8), storage));
+		}
+
+		// Set an entry of a map of pallet_kitties in the storage of the last block.
+		fn insert(&mut self, item: &[u8], kitty_id: u32, value: impl Encode) {
+			let key = [storage_prefix(PALLET_PREFIX, item), map_key(kitty_id)].concat();
+			self.blocks.last_mut().unwrap().1.insert(key, value.encode());
+		}
+
+		fn remove(&mut self, item: &[u8], kitty_id: u32) {
+			let key = [storage_prefix(PALLET_PREFIX, item), map_key(kitty_id)].concat();
+			self.blocks.last_mut().unwrap().1.remove(&key);
+		}
+
+		fn at(&self, hash: Hash) -> Result<&BTreeMap<Vec<u8>, Vec<u8>>> {
+			Ok(&self.blocks.iter().find(|b| b.0 == hash).ok_or("Unknown block")?.1)
+		}
+	}
+
+	impl Chain for TestChain {
+		fn finalized_head(&self) -> Result<(Hash, BlockNumber)> {
+			let head = self.blocks.len() as BlockNumber - 1;
+			Ok((self.blocks[head as usize].0, head))
+		}
+
+		fn block_hash(&self, number: BlockNumber) -> Result<Hash> {
+			self.fetched.borrow_mut().push(number);
+			Ok(self.blocks.get(number as usize).ok_or("No block")?.0)
+		}
+
+		fn storage_raw(&self, key: &[u8], at: Hash) -> Result<Option<Vec<u8>>> {
+			Ok(self.at(at)?.get(key).cloned())
+		}
+
+		fn storage_keys(&self, prefix: &[u8], at: Hash) -> Result<Vec<Vec<u8>>> {
+			Ok(self.at(at)?.keys().filter(|key| key.starts_with(prefix)).cloned().collect())
+		}
+	}
+
+	fn account(i: u8) -> AccountId {
+		AccountId::new([i; 32])
+	}
+
+	fn owners(db: &mut db::Db) -> Vec<(u32, String)> {
+		let tx = db.transaction().unwrap();
+		let mut stmt = tx.prepare("SELECT id, owner FROM kitties ORDER BY id").unwrap();
+		let mut owners = Vec::new();
+		for row in stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap() {
+			owners.push(row.unwrap());
+		}
+		owners
+	}
+
+	fn count(db: &mut db::Db, table: &str) -> u32 {
+		db.transaction().unwrap()
+			.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+			.unwrap()
+	}
+
+	// Genesis with kitty 0 of AccountID =1 listed for 100, then block 1 where AccountID =2
+	// creates kitty 1 and buys kitty 0.
+	fn test_chain() -> TestChain {
+		let price: Balance = 100;
+		let mut chain = TestChain::default();
+		chain.push_block(vec![]);
+		chain.insert(b"Kitties", 0, Kitty([0; 16]));
+		chain.insert(b"Owner", 0, account(1));
+		chain.insert(b"ListForSale", 0, price);
+		chain.push_block(vec![
+			KittyEvent::KittyCreated { owner: account(2), kitty_id: 1, dna: [1; 16] },
+			KittyEvent::KittySold(account(1), account(2), 0, price),
+			KittyEvent::KittyDelisted { kitty_id: 0, owner: account(1) },
+		]);
+		chain.insert(b"Kitties", 1, Kitty([1; 16]));
+		chain.insert(b"Owner", 0, account(2));
+		chain.insert(b"Owner", 1, account(2));
+		chain.remove(b"ListForSale", 0);
+		let sale: SaleRecord<AccountId, Balance, BlockNumber> = SaleRecord { seller: account(1), buyer: account(2), price, block: 1 };
+		chain.insert(b"KittySales", 0, vec![sale]);
+		chain
+	}
+
+	#[test]
+	fn snapshot_and_events_are_indexed() {
+		let chain = test_chain();
+		let mut db = db::Db::open(":memory:").unwrap();
+		// Start from a snapshot at genesis, as if block 1 wasn't finalized yet.
+		snapshot(&chain, &mut db, chain.blocks[0].0, 0).unwrap();
+		assert_eq!(owners(&mut db), vec![(0, account(1).to_string())]);
+		assert_eq!(count(&mut db, "listings"), 1);
+
+		sync(&chain, &mut db).unwrap();
+		assert_eq!(db.last_block().unwrap(), Some(1));
+		assert_eq!(owners(&mut db), vec![(0, account(2).to_string()), (1, account(2).to_string())]);
+		assert_eq!(count(&mut db, "listings"), 0);
+		assert_eq!(count(&mut db, "sales"), 1);
+	}
+
+	#[test]
+	fn sync_restarts_from_the_last_indexed_block() {
+		let path = std::env::temp_dir().join(format!("kitties-indexer-test-{}.db", std::process::id()));
+		let path = path.to_str().unwrap();
+		let _ = std::fs::remove_file(path);
+		let mut chain = test_chain();
+		{
+			let mut db = db::Db::open(path).unwrap();
+			// An empty database starts from a snapshot at the finalized head, block 1.
+			sync(&chain, &mut db).unwrap();
+			assert_eq!(db.last_block().unwrap(), Some(1));
+			assert_eq!(owners(&mut db), vec![(0, account(2).to_string()), (1, account(2).to_string())]);
+			assert_eq!(count(&mut db, "sales"), 1);
+		}
+
+		chain.push_block(vec![KittyEvent::KittyTransferred(account(2), account(3), 1)]);
+		chain.push_block(vec![KittyEvent::KittyTransferred(account(3), account(1), 1)]);
+		let mut db = db::Db::open(path).unwrap();
+		sync(&chain, &mut db).unwrap();
+		// Only the blocks after the last indexed one are fetched.
+		assert_eq!(*chain.fetched.borrow(), vec![2, 3]);
+		assert_eq!(db.last_block().unwrap(), Some(3));
+		assert_eq!(owners(&mut db), vec![(0, account(2).to_string()), (1, account(1).to_string())]);
+		// The sale of block 1 is in the snapshot, and its event isn't applied again.
+		assert_eq!(count(&mut db, "sales"), 1);
+
+		drop(db);
+		let _ = std::fs::remove_file(path);
+	}
+}
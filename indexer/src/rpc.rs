@@ -0,0 +1,101 @@
+//! A minimal JSON-RPC client of the node over HTTP.
+
+use codec::Decode;
+use serde_json::{json, Value};
+use node_template_runtime::{BlockNumber, Hash};
+use crate::Result;
+
+/// The number of storage keys fetched by each `state_getKeysPaged`.
+const KEYS_PAGE_SIZE: u32 = 1_000;
+
+/// The queries of the indexer to the chain.
+pub trait Chain {
+	/// The hash and the number of the latest finalized block.
+	fn finalized_head(&self) -> Result<(Hash, BlockNumber)>;
+
+	/// The hash of the block at `number` on the canonical chain.
+	fn block_hash(&self, number: BlockNumber) -> Result<Hash>;
+
+	/// The encoded storage value at `key`, at the block `at`.
+	fn storage_raw(&self, key: &[u8], at: Hash) -> Result<Option<Vec<u8>>>;
+
+	/// All the storage keys with `prefix`, at the block `at`.
+	fn storage_keys(&self, prefix: &[u8], at: Hash) -> Result<Vec<Vec<u8>>>;
+
+	/// The decoded storage value at `key`, at the block `at`.
+	fn storage<T: Decode>(&self, key: &[u8], at: Hash) -> Result<Option<T>> {
+		match self.storage_raw(key, at)? {
+			Some(value) => Ok(Some(T::decode(&mut &value[..])?)),
+			None => Ok(None),
+		}
+	}
+}
+
+pub struct Client {
+	url: String,
+}
+
+impl Client {
+	pub fn new(url: &str) -> Self {
+		Client { url: url.to_string() }
+	}
+
+	fn call(&self, method: &str, params: Value) -> Result<Value> {
+		let response: Value = ureq::post(&self.url)
+			.send_json(json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))?
+			.into_json()?;
+		if let Some(error) = response.get("error") {
+			return Err(format!("{} failed: {}", method, error).into());
+		}
+		Ok(response["result"].clone())
+	}
+}
+
+impl Chain for Client {
+	fn finalized_head(&self) -> Result<(Hash, BlockNumber)> {
+		let hash: Hash = serde_json::from_value(self.call("chain_getFinalizedHead", json!([]))?)?;
+		let header = self.call("chain_getHeader", json!([hash]))?;
+		let number = header["number"].as_str().ok_or("The header has no number")?;
+		Ok((hash, BlockNumber::from_str_radix(number.trim_start_matches("0x"), 16)?))
+	}
+
+	fn block_hash(&self, number: BlockNumber) -> Result<Hash> {
+		let hash = self.call("chain_getBlockHash", json!([number]))?;
+		if hash.is_null() {
+			return Err(format!("No block #{}", number).into());
+		}
+		Ok(serde_json::from_value(hash)?)
+	}
+
+	fn storage_raw(&self, key: &[u8], at: Hash) -> Result<Option<Vec<u8>>> {
+		match self.call("state_getStorage", json!([to_hex(key), at]))?.as_str() {
+			Some(value) => Ok(Some(from_hex(value)?)),
+			None => Ok(None),
+		}
+	}
+
+	fn storage_keys(&self, prefix: &[u8], at: Hash) -> Result<Vec<Vec<u8>>> {
+		let mut keys = Vec::new();
+		loop {
+			let start_key = keys.last().map(|key: &Vec<u8>| to_hex(key));
+			let page: Vec<String> = serde_json::from_value(
+				self.call("state_getKeysPaged", json!([to_hex(prefix), KEYS_PAGE_SIZE, start_key, at]))?
+			)?;
+			let len = page.len();
+			for key in page {
+				keys.push(from_hex(&key)?);
+			}
+			if len < KEYS_PAGE_SIZE as usize {
+				return Ok(keys);
+			}
+		}
+	}
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+	format!("0x{}", hex::encode(bytes))
+}
+
+fn from_hex(value: &str) -> Result<Vec<u8>> {
+	Ok(hex::decode(value.trim_start_matches("0x"))?)
+}
@@ -1,5 +1,6 @@
 use structopt::StructOpt;
 use sc_cli::RunCmd;
+use crate::sealing::Sealing;
 
 #[derive(Debug, StructOpt)]
 pub struct Cli {
@@ -8,6 +9,11 @@ pub struct Cli {
 
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// How the blocks are sealed: aura, instant, manual or interval=<ms>.
+	/// All but aura seal the blocks without slots or GRANDPA, for development and tests.
+	#[structopt(long, default_value = "aura")]
+	pub sealing: Sealing,
 }
 
 #[derive(Debug, StructOpt)]
@@ -35,7 +41,22 @@ pub enum Subcommand {
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
+	/// Export the kitties, owners and listings at a block as JSON or CSV.
+	ExportKitties(crate::kitties::ExportKittiesCmd),
+
+	/// Write a chain spec with the kitties of an exported file in its genesis.
+	ImportKitties(crate::kitties::ImportKittiesCmd),
+
 	/// The custom benchmark subcommmand benchmarking runtime pallets.
 	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
+
+	/// Try the runtime upgrade against the state of a live chain or a snapshot file.
+	#[cfg(feature = "try-runtime")]
+	TryRuntime(try_runtime_cli::TryRuntimeCmd),
+
+	/// Try the runtime upgrade against the state of a live chain or a snapshot file.
+	/// The node must be built with `--features try-runtime`.
+	#[cfg(not(feature = "try-runtime"))]
+	TryRuntime,
 }
@@ -0,0 +1,324 @@
+//! Export and import of the kitties, to fork a testnet with real user data.
+
+use std::{collections::BTreeMap, fs, io::Write, path::PathBuf, str::FromStr, sync::Arc};
+use codec::Decode;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use structopt::StructOpt;
+use sc_cli::{CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::{Backend, StorageProvider};
+use sp_blockchain::HeaderBackend;
+use sp_core::{crypto::Ss58Codec, hashing::twox_128, storage::StorageKey};
+use sp_runtime::generic::BlockId;
+use node_template_runtime::{AccountId, Balance, Block, BlockNumber};
+use pallet_kitties::Lineage;
+
+/// The name of pallet_kitties in `construct_runtime!`, the prefix of its storage.
+const PALLET_PREFIX: &[u8] = b"KittiesModule";
+
+/// A kitty in an exported file.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KittyRecord {
+	pub id: u32,
+	/// The SS58 address of the owner.
+	pub owner: String,
+	/// The DNA in hex.
+	pub dna: String,
+	/// The list price, if the kitty is for sale. It's a string, as it may not fit in a JSON number.
+	pub price: Option<String>,
+	/// The generation, if the lineage of the kitty is known.
+	pub generation: Option<u32>,
+	/// The parents, if the kitty was bred and its lineage is known.
+	pub parents: Option<(u32, u32)>,
+}
+
+/// The format of an exported file.
+#[derive(Debug, Clone, Copy)]
+pub enum Format {
+	Json,
+	Csv,
+}
+
+impl FromStr for Format {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"json" => Ok(Format::Json),
+			"csv" => Ok(Format::Csv),
+			_ => Err(format!("Unknown format {}, expected json or csv", s)),
+		}
+	}
+}
+
+/// The `export-kitties` command.
+#[derive(Debug, StructOpt)]
+pub struct ExportKittiesCmd {
+	/// The number of the block to export at. Defaults to the best block.
+	#[structopt(long)]
+	pub at: Option<BlockNumber>,
+
+	/// The format of the output: json or csv.
+	#[structopt(long, default_value = "json")]
+	pub format: Format,
+
+	/// The output file. Defaults to stdout.
+	#[structopt(long, parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl ExportKittiesCmd {
+	/// Export the kitties, owners and listings at the block.
+	pub async fn run<B, C>(&self, client: Arc<C>) -> sc_cli::Result<()>
+	where
+		B: Backend<Block>,
+		C: StorageProvider<Block, B> + HeaderBackend<Block>,
+	{
+		let at = match self.at {
+			Some(number) => BlockId::Number(number),
+			None => BlockId::Hash(client.info().best_hash),
+		};
+
+		let owners: BTreeMap<u32, AccountId> = storage_map(&*client, &at, b"Owner")?.into_iter().collect();
+		let prices: BTreeMap<u32, Balance> = storage_map(&*client, &at, b"ListForSale")?.into_iter().collect();
+		let lineages: BTreeMap<u32, Lineage<u32>> = storage_map(&*client, &at, b"KittyLineage")?.into_iter().collect();
+		let mut records = Vec::new();
+		for (id, dna) in storage_map::<[u8; 16], _, _>(&*client, &at, b"Kitties")? {
+			let owner = owners.get(&id).ok_or_else(|| format!("Kitty {} has no owner", id))?;
+			records.push(KittyRecord {
+				id,
+				owner: owner.to_ss58check(),
+				dna: format!("0x{}", hex::encode(dna)),
+				price: prices.get(&id).map(|price| price.to_string()),
+				generation: lineages.get(&id).map(|lineage| lineage.generation),
+				parents: lineages.get(&id).and_then(|lineage| lineage.parents),
+			});
+		}
+		records.sort_by_key(|record| record.id);
+
+		let output = match self.format {
+			Format::Json => serde_json::to_string_pretty(&records).map_err(|e| e.to_string())?,
+			Format::Csv => to_csv(&records),
+		};
+		write_output(&self.output, &output)
+	}
+}
+
+impl CliConfiguration for ExportKittiesCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+/// The `import-kitties` command.
+#[derive(Debug, StructOpt)]
+pub struct ImportKittiesCmd {
+	/// The file exported by `export-kitties`.
+	#[structopt(long, parse(from_os_str))]
+	pub input: PathBuf,
+
+	/// The format of the input: json or csv.
+	#[structopt(long, default_value = "json")]
+	pub format: Format,
+
+	/// The output chain spec file. Defaults to stdout.
+	#[structopt(long, parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl ImportKittiesCmd {
+	/// Write the chain spec given by `--chain`, with the kitties in the pallet_kitties genesis.
+	/// The owners who can't pay the stake at genesis hold their kitties without stake.
+	pub fn run(&self, spec: Box<dyn sc_service::ChainSpec>) -> sc_cli::Result<()> {
+		let input = fs::read_to_string(&self.input)?;
+		let records = match self.format {
+			Format::Json => serde_json::from_str(&input).map_err(|e| e.to_string())?,
+			Format::Csv => from_csv(&input)?,
+		};
+
+		let kitties = genesis_kitties(records)?;
+
+		let mut spec: Value = serde_json::from_str(&spec.as_json(false)?).map_err(|e| e.to_string())?;
+		let runtime = spec.pointer_mut("/genesis/runtime")
+			.and_then(Value::as_object_mut)
+			.ok_or("The chain spec must not be raw")?;
+		runtime.insert("kittiesModule".into(), json!({ "kitties": KITTIES_PLACEHOLDER }));
+
+		// A `Value` can't hold the prices above u64::MAX, so the kitties are written as text.
+		let output = serde_json::to_string_pretty(&spec)
+			.map_err(|e| e.to_string())?
+			.replacen(&format!("\"{}\"", KITTIES_PLACEHOLDER), &kitties, 1);
+		write_output(&self.output, &output)
+	}
+}
+
+/// The placeholder of the kitties in the chain spec, replaced by the JSON of `genesis_kitties`.
+const KITTIES_PLACEHOLDER: &str = "<kitties>";
+
+// The JSON of the kitties in the pallet_kitties genesis: [kitty_id, owner, dna, price, lineage].
+fn genesis_kitties(records: Vec<KittyRecord>) -> sc_cli::Result<String> {
+	let mut kitties = Vec::new();
+	for record in records {
+		let owner = AccountId::from_ss58check(&record.owner)
+			.map_err(|e| format!("Invalid owner of kitty {}: {:?}", record.id, e))?;
+		let mut dna = [0u8; 16];
+		hex::decode_to_slice(record.dna.trim_start_matches("0x"), &mut dna)
+			.map_err(|e| format!("Invalid DNA of kitty {}: {}", record.id, e))?;
+		let price = record.price
+			.map(|price| price.parse::<Balance>())
+			.transpose()
+			.map_err(|e| format!("Invalid price of kitty {}: {}", record.id, e))?;
+		let lineage = record.generation.map(|generation| (record.parents, generation));
+		kitties.push((record.id, owner, dna, price, lineage));
+	}
+	serde_json::to_string(&kitties).map_err(|e| e.to_string().into())
+}
+
+impl CliConfiguration for ImportKittiesCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
+
+// All the entries of a `Blake2_128Concat` map of pallet_kitties keyed by the kitty index.
+fn storage_map<V, B, C>(client: &C, at: &BlockId<Block>, item: &[u8]) -> sc_cli::Result<Vec<(u32, V)>>
+where
+	V: Decode,
+	B: Backend<Block>,
+	C: StorageProvider<Block, B>,
+{
+	let prefix = [twox_128(PALLET_PREFIX), twox_128(item)].concat();
+	let pairs = client.storage_pairs(at, &StorageKey(prefix.clone()))
+		.map_err(|e| e.to_string())?;
+	pairs.into_iter()
+		.map(|(key, value)| {
+			// Skip the prefix and the blake2_128 hash of the key.
+			let id = u32::decode(&mut &key.0[prefix.len() + 16..])?;
+			Ok((id, V::decode(&mut &value.0[..])?))
+		})
+		.collect::<Result<_, codec::Error>>()
+		.map_err(|e| e.to_string().into())
+}
+
+// The parents are written as `parent1-parent2`.
+fn to_csv(records: &[KittyRecord]) -> String {
+	let mut csv = String::from("id,owner,dna,price,generation,parents\n");
+	for record in records {
+		csv.push_str(&format!(
+			"{},{},{},{},{},{}\n",
+			record.id,
+			record.owner,
+			record.dna,
+			record.price.as_deref().unwrap_or(""),
+			record.generation.map(|generation| generation.to_string()).unwrap_or_default(),
+			record.parents.map(|(parent1, parent2)| format!("{}-{}", parent1, parent2)).unwrap_or_default(),
+		));
+	}
+	csv
+}
+
+fn from_csv(csv: &str) -> sc_cli::Result<Vec<KittyRecord>> {
+	csv.lines()
+		.skip(1)
+		.filter(|line| !line.trim().is_empty())
+		.map(|line| -> sc_cli::Result<KittyRecord> {
+			let fields: Vec<&str> = line.trim().split(',').collect();
+			let invalid = |field: &str| format!("Invalid {} in: {}", field, line);
+			// The files exported before the lineage have no generation and parents.
+			let (id, owner, dna, price, generation, parents) = match fields[..] {
+				[id, owner, dna, price] => (id, owner, dna, price, "", ""),
+				[id, owner, dna, price, generation, parents] => (id, owner, dna, price, generation, parents),
+				_ => return Err(format!("Expected id,owner,dna,price,generation,parents in: {}", line).into()),
+			};
+			let parents = match parents.split_once('-') {
+				Some((parent1, parent2)) => Some((
+					parent1.parse().map_err(|_| invalid("parents"))?,
+					parent2.parse().map_err(|_| invalid("parents"))?,
+				)),
+				None if parents.is_empty() => None,
+				None => return Err(invalid("parents").into()),
+			};
+			Ok(KittyRecord {
+				id: id.parse().map_err(|_| invalid("kitty id"))?,
+				owner: owner.into(),
+				dna: dna.into(),
+				price: Some(price).filter(|price| !price.is_empty()).map(Into::into),
+				generation: Some(generation)
+					.filter(|generation| !generation.is_empty())
+					.map(|generation| generation.parse().map_err(|_| invalid("generation")))
+					.transpose()?,
+				parents,
+			})
+		})
+		.collect()
+}
+
+fn write_output(path: &Option<PathBuf>, output: &str) -> sc_cli::Result<()> {
+	match path {
+		Some(path) => fs::write(path, output)?,
+		None => std::io::stdout().write_all(output.as_bytes())?,
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::crypto::AccountId32;
+
+	#[test]
+	fn large_price_and_lineage_round_trip() {
+		let owner = AccountId32::new([1; 32]);
+		let record = |id, price: Option<Balance>, generation, parents| KittyRecord {
+			id,
+			owner: owner.to_ss58check(),
+			dna: format!("0x{}", hex::encode([2u8; 16])),
+			price: price.map(|price| price.to_string()),
+			generation,
+			parents,
+		};
+		let records = vec![
+			record(7, Some(Balance::max_value()), Some(2), Some((3, 5))),
+			record(8, None, None, None),
+		];
+
+		let records = from_csv(&to_csv(&records)).unwrap();
+		let kitties: Vec<(u32, AccountId, [u8; 16], Option<Balance>, Option<(Option<(u32, u32)>, u32)>)> =
+			serde_json::from_str(&genesis_kitties(records).unwrap()).unwrap();
+		assert_eq!(kitties, vec![
+			(7, owner.clone(), [2; 16], Some(Balance::max_value()), Some((Some((3, 5)), 2))),
+			(8, owner, [2; 16], None, None),
+		]);
+	}
+
+	#[test]
+	fn csv_without_lineage_is_read() {
+		let records = from_csv("id,owner,dna,price\n7,owner,0x00,\n").unwrap();
+		assert_eq!(records[0].generation, None);
+		assert_eq!(records[0].parents, None);
+	}
+}
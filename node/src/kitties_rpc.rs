@@ -0,0 +1,105 @@
+//! The `kitties_*` RPC methods.
+
+use std::sync::Arc;
+use codec::Decode;
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::offchain::OffchainStorage;
+use sp_runtime::generic::BlockId;
+use node_template_runtime::{opaque::Block, AccountId, Balance, BlockNumber, Hash};
+use pallet_kitties::metadata::CachedMetadata;
+use pallet_kitties_runtime_api::KittiesApi as KittiesRuntimeApi;
+
+/// The error code of a failed runtime API call.
+const RUNTIME_ERROR: i64 = 1;
+
+#[rpc(server)]
+pub trait KittiesApi {
+	/// The SVG image of a kitty, drawn from its DNA by `kitties-render`.
+	/// At the block `at`, the best block by default.
+	#[rpc(name = "kitties_renderSvg")]
+	fn render_svg(&self, kitty_id: u32, at: Option<Hash>) -> Result<String>;
+
+	/// The metadata JSON of a kitty, cached by the offchain worker of pallet_kitties.
+	/// None if the offchain worker hasn't cached it yet, or cached another kitty with the same id
+	/// before a reorg.
+	#[rpc(name = "kitties_metadata")]
+	fn metadata(&self, kitty_id: u32) -> Result<Option<serde_json::Value>>;
+}
+
+/// The `kitties_*` RPC methods, with the runtime API of the kitties
+/// and the offchain local storage, if the node has one.
+pub struct Kitties<C, S> {
+	client: Arc<C>,
+	offchain_storage: Option<S>,
+}
+
+impl<C, S> Kitties<C, S> {
+	pub fn new(client: Arc<C>, offchain_storage: Option<S>) -> Self {
+		Kitties { client, offchain_storage }
+	}
+}
+
+impl<C, S> Kitties<C, S>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: KittiesRuntimeApi<Block, u32, Balance, AccountId, BlockNumber>,
+{
+	fn dna(&self, kitty_id: u32, at: Option<Hash>) -> Result<[u8; 16]> {
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		self.client.runtime_api().dna(&at, kitty_id)
+			.map_err(|e| Error {
+				code: ErrorCode::ServerError(RUNTIME_ERROR),
+				message: "Unable to query the kitty.".into(),
+				data: Some(format!("{:?}", e).into()),
+			})?
+			.ok_or_else(|| Error {
+				code: ErrorCode::InvalidParams,
+				message: format!("No kitty {}", kitty_id),
+				data: None,
+			})
+	}
+}
+
+impl<C, S> KittiesApi for Kitties<C, S>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: KittiesRuntimeApi<Block, u32, Balance, AccountId, BlockNumber>,
+	S: OffchainStorage + 'static,
+{
+	fn render_svg(&self, kitty_id: u32, at: Option<Hash>) -> Result<String> {
+		Ok(kitties_render::render_svg(&self.dna(kitty_id, at)?))
+	}
+
+	fn metadata(&self, kitty_id: u32) -> Result<Option<serde_json::Value>> {
+		// The metadata of the released kitties stays in the offchain storage.
+		let dna = self.dna(kitty_id, None)?;
+
+		let storage = self.offchain_storage.as_ref().ok_or_else(|| Error {
+			code: ErrorCode::MethodNotFound,
+			message: "The node has no offchain storage.".into(),
+			data: None,
+		})?;
+		let entry = match storage.get(sp_offchain::STORAGE_PREFIX, &pallet_kitties::metadata::metadata_key(&kitty_id)) {
+			Some(entry) => entry,
+			None => return Ok(None),
+		};
+		let cached = CachedMetadata::decode(&mut &entry[..]).map_err(|e| Error {
+			code: ErrorCode::InternalError,
+			message: "Invalid metadata in the offchain storage.".into(),
+			data: Some(e.to_string().into()),
+		})?;
+		// The offchain worker caches the kitty of the best chain again after a reorg.
+		if cached.dna != dna {
+			return Ok(None);
+		}
+
+		serde_json::from_slice(&cached.json).map(Some).map_err(|e| Error {
+			code: ErrorCode::InternalError,
+			message: "Invalid metadata in the offchain storage.".into(),
+			data: Some(e.to_string().into()),
+		})
+	}
+}
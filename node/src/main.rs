@@ -6,7 +6,10 @@ mod chain_spec;
 mod service;
 mod cli;
 mod command;
+mod kitties;
+mod kitties_rpc;
 mod rpc;
+mod sealing;
 
 fn main() -> sc_cli::Result<()> {
 	command::run()
@@ -7,44 +7,56 @@
 
 use std::sync::Arc;
 
-use node_template_runtime::{opaque::Block, AccountId, Balance, Index};
+use futures::channel::mpsc;
+use node_template_runtime::{opaque::Block, AccountId, Balance, BlockNumber, Hash, Index};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::{Error as BlockChainError, HeaderMetadata, HeaderBackend};
 use sp_block_builder::BlockBuilder;
 pub use sc_rpc_api::DenyUnsafe;
 use sp_transaction_pool::TransactionPool;
+use sc_consensus_manual_seal::EngineCommand;
+use sp_core::offchain::OffchainStorage;
 
 
 /// Full client dependencies.
-pub struct FullDeps<C, P> {
+pub struct FullDeps<C, P, S> {
 	/// The client instance to use.
 	pub client: Arc<C>,
 	/// Transaction pool instance.
 	pub pool: Arc<P>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// The commands of the `engine_*` RPCs, when the blocks are sealed by manual seal.
+	pub command_sink: Option<mpsc::Sender<EngineCommand<Hash>>>,
+	/// The offchain local storage, where the offchain workers cache the kitty metadata.
+	pub offchain_storage: Option<S>,
 }
 
 /// Instantiate all full RPC extensions.
-pub fn create_full<C, P>(
-	deps: FullDeps<C, P>,
+pub fn create_full<C, P, S>(
+	deps: FullDeps<C, P, S>,
 ) -> jsonrpc_core::IoHandler<sc_rpc::Metadata> where
 	C: ProvideRuntimeApi<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error=BlockChainError> + 'static,
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: pallet_kitties_runtime_api::KittiesApi<Block, u32, Balance, AccountId, BlockNumber>,
 	C::Api: BlockBuilder<Block>,
 	P: TransactionPool + 'static,
+	S: OffchainStorage + 'static,
 {
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
+	use crate::kitties_rpc::{Kitties, KittiesApi};
 
 	let mut io = jsonrpc_core::IoHandler::default();
 	let FullDeps {
 		client,
 		pool,
 		deny_unsafe,
+		command_sink,
+		offchain_storage,
 	} = deps;
 
 	io.extend_with(
@@ -55,6 +67,19 @@ pub fn create_full<C, P>(
 		TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone()))
 	);
 
+	io.extend_with(
+		KittiesApi::to_delegate(Kitties::new(client.clone(), offchain_storage))
+	);
+
+	if let Some(command_sink) = command_sink {
+		use sc_consensus_manual_seal::rpc::{ManualSeal, ManualSealApi};
+
+		// `engine_createBlock` and `engine_finalizeBlock`.
+		io.extend_with(
+			ManualSealApi::to_delegate(ManualSeal::new(command_sink))
+		);
+	}
+
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
 	// to call into the runtime.
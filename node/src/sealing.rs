@@ -0,0 +1,153 @@
+//! Block sealing without Aura slots, for development and integration tests.
+
+use std::{
+	str::FromStr,
+	sync::{Arc, atomic::{AtomicU64, Ordering}},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use codec::Encode;
+use futures::{channel::mpsc, stream::{self, BoxStream}, StreamExt};
+use sc_consensus_manual_seal::{ConsensusDataProvider, EngineCommand, Error};
+use sp_consensus::BlockImportParams;
+use sp_consensus_aura::{AURA_ENGINE_ID, Slot};
+use sp_inherents::InherentData;
+use sp_runtime::{generic::{Digest, DigestItem}, traits::Block as BlockT};
+use node_template_runtime::{opaque::Block, Hash};
+
+/// How the blocks are sealed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sealing {
+	/// Aura slots, finalized by GRANDPA.
+	Aura,
+	/// A block as soon as a transaction enters the pool.
+	Instant,
+	/// A block on each `engine_createBlock` RPC.
+	Manual,
+	/// A block every given milliseconds.
+	Interval(u64),
+}
+
+impl Sealing {
+	/// Whether the blocks are sealed by `sc-consensus-manual-seal`.
+	pub fn is_manual_seal(&self) -> bool {
+		*self != Sealing::Aura
+	}
+}
+
+impl FromStr for Sealing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s {
+			"aura" => Sealing::Aura,
+			"instant" => Sealing::Instant,
+			"manual" => Sealing::Manual,
+			_ => match s.strip_prefix("interval=").map(str::parse) {
+				Some(Ok(millis)) if millis > 0 => Sealing::Interval(millis),
+				_ => return Err(format!(
+					"Unknown sealing {}, expected aura, instant, manual or interval=<ms>", s
+				)),
+			},
+		})
+	}
+}
+
+/// The stream of commands to seal the blocks. The `engine_*` RPCs send to `rpc_commands`
+/// with any sealing, besides the blocks sealed by the transactions or the interval.
+/// The blocks sealed automatically are finalized at once, as there's no GRANDPA.
+pub fn commands_stream<P>(
+	sealing: Sealing,
+	pool: &P,
+	rpc_commands: mpsc::Receiver<EngineCommand<Hash>>,
+) -> BoxStream<'static, EngineCommand<Hash>>
+where
+	P: sp_transaction_pool::TransactionPool,
+{
+	let seal = |create_empty| EngineCommand::SealNewBlock {
+		create_empty,
+		finalize: true,
+		parent_hash: None,
+		sender: None,
+	};
+
+	match sealing {
+		Sealing::Instant => stream::select(
+			pool.import_notification_stream().map(move |_| seal(false)),
+			rpc_commands,
+		).boxed(),
+		Sealing::Interval(millis) => stream::select(
+			stream::unfold((), move |()| async move {
+				futures_timer::Delay::new(Duration::from_millis(millis)).await;
+				Some((seal(true), ()))
+			}),
+			rpc_commands,
+		).boxed(),
+		Sealing::Aura | Sealing::Manual => rpc_commands.boxed(),
+	}
+}
+
+/// The timestamps of the sealed blocks. They follow the system time, but are at least a slot
+/// apart, so the Aura slot increases with each block however fast the blocks are sealed.
+#[derive(Clone)]
+pub struct SlotTimestamps {
+	last: Arc<AtomicU64>,
+	slot_duration: u64,
+}
+
+impl SlotTimestamps {
+	pub fn new(slot_duration: u64) -> Self {
+		SlotTimestamps { last: Arc::new(AtomicU64::new(0)), slot_duration }
+	}
+
+	/// The timestamp of the next block, in milliseconds.
+	pub fn next(&self) -> u64 {
+		let now = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.expect("The system time is after the UNIX epoch; qed")
+			.as_millis() as u64;
+		let mut last = self.last.load(Ordering::SeqCst);
+		loop {
+			let next = now.max(last + self.slot_duration);
+			match self.last.compare_exchange(last, next, Ordering::SeqCst, Ordering::SeqCst) {
+				Ok(_) => return next,
+				Err(actual) => last = actual,
+			}
+		}
+	}
+}
+
+/// Adds the Aura pre-digest of the slot in the inherent data to the sealed blocks,
+/// as `pallet_aura` requires one for each block.
+pub struct AuraDigestProvider<Transaction>(std::marker::PhantomData<fn() -> Transaction>);
+
+impl<Transaction> Default for AuraDigestProvider<Transaction> {
+	fn default() -> Self {
+		AuraDigestProvider(Default::default())
+	}
+}
+
+impl<Transaction: Send + Sync + 'static> ConsensusDataProvider<Block> for AuraDigestProvider<Transaction> {
+	type Transaction = Transaction;
+
+	fn create_digest(
+		&self,
+		_parent: &<Block as BlockT>::Header,
+		inherents: &InherentData,
+	) -> Result<Digest<Hash>, Error> {
+		let slot = inherents
+			.get_data::<Slot>(&sp_consensus_aura::inherents::INHERENT_IDENTIFIER)
+			.map_err(|e| Error::StringError(e.to_string()))?
+			.ok_or_else(|| Error::StringError("No Aura slot in the inherent data".into()))?;
+
+		Ok(Digest { logs: vec![DigestItem::PreRuntime(AURA_ENGINE_ID, slot.encode())] })
+	}
+
+	fn append_block_import(
+		&self,
+		_parent: &<Block as BlockT>::Header,
+		_params: &mut BlockImportParams<Block, Self::Transaction>,
+		_inherents: &InherentData,
+	) -> Result<(), Error> {
+		Ok(())
+	}
+}
@@ -1,7 +1,7 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
 use std::{sync::Arc, time::Duration};
-use sc_client_api::{ExecutorProvider, RemoteBackend};
+use sc_client_api::{Backend, ExecutorProvider, RemoteBackend};
 use node_template_runtime::{self, opaque::Block, RuntimeApi};
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
 use sc_executor::native_executor_instance;
@@ -12,6 +12,8 @@ use sc_finality_grandpa::SharedVoterState;
 use sc_keystore::LocalKeystore;
 use sc_telemetry::{Telemetry, TelemetryWorker};
 use sp_consensus::SlotData;
+use sc_consensus_manual_seal::ManualSealParams;
+use crate::sealing::{AuraDigestProvider, Sealing, SlotTimestamps};
 
 // Our native executor instance.
 native_executor_instance!(
@@ -25,7 +27,7 @@ type FullClient = sc_service::TFullClient<Block, RuntimeApi, Executor>;
 type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
 
-pub fn new_partial(config: &Configuration) -> Result<sc_service::PartialComponents<
+pub fn new_partial(config: &Configuration, sealing: Sealing) -> Result<sc_service::PartialComponents<
 	FullClient, FullBackend, FullSelectChain,
 	sp_consensus::DefaultImportQueue<Block, FullClient>,
 	sc_transaction_pool::FullPool<Block, FullClient>,
@@ -81,29 +83,37 @@ pub fn new_partial(config: &Configuration) -> Result<sc_service::PartialComponen
 
 	let slot_duration = sc_consensus_aura::slot_duration(&*client)?.slot_duration();
 
-	let import_queue = sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _, _>(
-		ImportQueueParams {
-			block_import: grandpa_block_import.clone(),
-			justification_import: Some(Box::new(grandpa_block_import.clone())),
-			client: client.clone(),
-			create_inherent_data_providers: move |_, ()| async move {
-				let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+	let import_queue = if sealing.is_manual_seal() {
+		sc_consensus_manual_seal::import_queue(
+			Box::new(client.clone()),
+			&task_manager.spawn_essential_handle(),
+			config.prometheus_registry(),
+		)
+	} else {
+		sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _, _>(
+			ImportQueueParams {
+				block_import: grandpa_block_import.clone(),
+				justification_import: Some(Box::new(grandpa_block_import.clone())),
+				client: client.clone(),
+				create_inherent_data_providers: move |_, ()| async move {
+					let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
 
-				let slot =
-					sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_duration(
-						*timestamp,
-						slot_duration,
-					);
+					let slot =
+						sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_duration(
+							*timestamp,
+							slot_duration,
+						);
 
-				Ok((timestamp, slot))
+					Ok((timestamp, slot))
+				},
+				spawner: &task_manager.spawn_essential_handle(),
+				can_author_with: sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone()),
+				registry: config.prometheus_registry(),
+				check_for_equivocation: Default::default(),
+				telemetry: telemetry.as_ref().map(|x| x.handle()),
 			},
-			spawner: &task_manager.spawn_essential_handle(),
-			can_author_with: sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone()),
-			registry: config.prometheus_registry(),
-			check_for_equivocation: Default::default(),
-			telemetry: telemetry.as_ref().map(|x| x.handle()),
-		},
-	)?;
+		)?
+	};
 
 	Ok(sc_service::PartialComponents {
 		client,
@@ -125,7 +135,7 @@ fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
 }
 
 /// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+pub fn new_full(mut config: Configuration, sealing: Sealing) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -135,7 +145,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		select_chain,
 		transaction_pool,
 		other: (block_import, grandpa_link, mut telemetry),
-	} = new_partial(&config)?;
+	} = new_partial(&config, sealing)?;
 
 	if let Some(url) = &config.keystore_remote {
 		match remote_keystore(url) {
@@ -173,15 +183,26 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	let enable_grandpa = !config.disable_grandpa;
 	let prometheus_registry = config.prometheus_registry().cloned();
 
+	// The commands of the `engine_*` RPCs, when the blocks are sealed by manual seal.
+	let (command_sink, commands) = if sealing.is_manual_seal() {
+		let (sink, commands) = futures::channel::mpsc::channel(1024);
+		(Some(sink), Some(commands))
+	} else {
+		(None, None)
+	};
+
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
+		let offchain_storage = backend.offchain_storage();
 
 		Box::new(move |deny_unsafe, _| {
 			let deps = crate::rpc::FullDeps {
 				client: client.clone(),
 				pool: pool.clone(),
 				deny_unsafe,
+				command_sink: command_sink.clone(),
+				offchain_storage: offchain_storage.clone(),
 			};
 
 			crate::rpc::create_full(deps)
@@ -205,6 +226,52 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		},
 	)?;
 
+	if let Some(commands) = commands {
+		let proposer_factory = sc_basic_authorship::ProposerFactory::new(
+			task_manager.spawn_handle(),
+			client.clone(),
+			transaction_pool.clone(),
+			prometheus_registry.as_ref(),
+			telemetry.as_ref().map(|x| x.handle()),
+		);
+
+		let slot_duration = sc_consensus_aura::slot_duration(&*client)?.slot_duration();
+		let timestamps = SlotTimestamps::new(slot_duration);
+
+		let manual_seal = sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+			block_import: client.clone(),
+			env: proposer_factory,
+			client: client.clone(),
+			pool: transaction_pool.pool().clone(),
+			commands_stream: crate::sealing::commands_stream(sealing, &*transaction_pool, commands),
+			select_chain,
+			consensus_data_provider: Some(Box::new(
+				AuraDigestProvider::<sp_api::TransactionFor<FullClient, Block>>::default()
+			)),
+			create_inherent_data_providers: move |_, ()| {
+				let timestamps = timestamps.clone();
+				async move {
+					let timestamp = sp_timestamp::InherentDataProvider::new(timestamps.next().into());
+
+					let slot =
+						sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_duration(
+							*timestamp,
+							slot_duration,
+						);
+
+					Ok((timestamp, slot))
+				}
+			},
+		});
+
+		// Without Aura there's no GRANDPA either, the sealed blocks are finalized by the
+		// sealing itself or by `engine_finalizeBlock`.
+		task_manager.spawn_essential_handle().spawn_blocking("manual-seal", manual_seal);
+
+		network_starter.start_network();
+		return Ok(task_manager);
+	}
+
 	if role.is_authority() {
 		let proposer_factory = sc_basic_authorship::ProposerFactory::new(
 			task_manager.spawn_handle(),
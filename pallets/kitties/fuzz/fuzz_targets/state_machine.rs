@@ -0,0 +1,8 @@
+//! Random calls of pallet_kitties, checking the invariants after each call.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pallet_kitties::fuzzing::{run, Op};
+
+fuzz_target!(|ops: Vec<Op>| run(&ops));
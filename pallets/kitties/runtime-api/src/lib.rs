@@ -0,0 +1,27 @@
+//! Runtime API definition for the kitties pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use pallet_kitties::SaleRecord;
+
+sp_api::decl_runtime_apis! {
+	/// The API to query the kitties pallet.
+	pub trait KittiesApi<KittyIndex, Balance, AccountId, BlockNumber> where
+		KittyIndex: Codec,
+		Balance: Codec,
+		AccountId: Codec,
+		BlockNumber: Codec,
+	{
+		/// The number of gen-0 kitties which can still be minted.
+		fn remaining_gen0_supply() -> u32;
+		/// The number of kitties which can still be minted or bred.
+		fn remaining_supply() -> u32;
+		/// The lowest price of the kitties listed for sale.
+		fn floor_price() -> Option<Balance>;
+		/// The latest sale of a kitty.
+		fn last_sale(kitty_id: KittyIndex) -> Option<SaleRecord<AccountId, Balance, BlockNumber>>;
+		/// The DNA of a kitty, None if it doesn't exist.
+		fn dna(kitty_id: KittyIndex) -> Option<[u8; 16]>;
+	}
+}
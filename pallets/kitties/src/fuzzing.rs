@@ -0,0 +1,104 @@
+//! A state machine of random calls against the mock runtime, which checks the invariants of the
+//! pallet after each call. It's driven by the proptest harness in `tests.rs`, and by the
+//! `cargo fuzz` target in `fuzz`.
+
+use frame_support::traits::{Get, ReservableCurrency};
+use crate::mock::{
+	new_test_ext, run_to_block, Balances, KittiesModule, MaxGen0Supply, MaxSupply, MintEraLength, Origin,
+	StakeForEachKitty, System, Test,
+};
+use crate::pallet::*;
+
+/// The accounts of the calls: 1 and 2 are rich, 3 can pay one stake, 4 has no balance.
+pub const ACCOUNTS: [u64; 4] = [1, 2, 3, 4];
+
+/// The supply caps of the runs, so the long runs go on minting.
+const MAX_SUPPLY: u32 = 1_000;
+
+/// A call of an account, picked by the index `who` in `ACCOUNTS`, or the blocks up to the next
+/// mint era, which restarts the mint rate limit. The kitty ids go past the next id, for the calls
+/// on missing kitties.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub enum Op {
+	Create { who: u8 },
+	Breed { who: u8, kitty_1: u8, kitty_2: u8 },
+	Transfer { who: u8, to: u8, kitty: u8 },
+	Sell { who: u8, kitty: u8, price: Option<u16> },
+	Buy { who: u8, kitty: u8 },
+	NextEra,
+}
+
+/// Apply the calls in order to a new mock runtime, and check the invariants after each one.
+/// The calls may fail, e.g. on the kitty of another owner, but they never break the invariants.
+pub fn run(ops: &[Op]) {
+	new_test_ext().execute_with(|| {
+		MaxGen0Supply::set(&MAX_SUPPLY);
+		MaxSupply::set(&MAX_SUPPLY);
+		for op in ops {
+			apply(op);
+			check_invariants();
+		}
+	});
+}
+
+fn account(who: u8) -> u64 {
+	ACCOUNTS[who as usize % ACCOUNTS.len()]
+}
+
+fn kitty(kitty: u8) -> u32 {
+	kitty as u32 % (NextKittyId::<Test>::get() + 2)
+}
+
+fn apply(op: &Op) {
+	let _ = match *op {
+		Op::Create { who } => KittiesModule::create(Origin::signed(account(who))),
+		Op::Breed { who, kitty_1, kitty_2 } =>
+			KittiesModule::breed(Origin::signed(account(who)), kitty(kitty_1), kitty(kitty_2)),
+		Op::Transfer { who, to, kitty: id } =>
+			KittiesModule::transfer(Origin::signed(account(who)), account(to), kitty(id)),
+		Op::Sell { who, kitty: id, price } =>
+			KittiesModule::sell(Origin::signed(account(who)), kitty(id), price.map(u128::from)),
+		Op::Buy { who, kitty: id } => KittiesModule::buy(Origin::signed(account(who)), kitty(id)),
+		Op::NextEra => {
+			let era_length = MintEraLength::get();
+			run_to_block((System::block_number() / era_length + 1) * era_length);
+			Ok(())
+		},
+	};
+}
+
+/// The invariants of the kitties, owners, listings and stakes.
+pub fn check_invariants() {
+	assert_eq!(KittiesModule::do_try_state(), Ok(()));
+
+	let kitties: Vec<u32> = Kitties::<Test>::iter_keys().collect();
+
+	// Every kitty has exactly one owner, and every owner has a kitty.
+	for kitty_id in &kitties {
+		assert!(Owner::<Test>::contains_key(kitty_id), "Kitty {} has no owner", kitty_id);
+	}
+	assert_eq!(Owner::<Test>::iter_keys().count(), kitties.len(), "An owner has no kitty");
+
+	// The counters match the stored kitties.
+	assert_eq!(TotalLiveKitties::<Test>::get() as usize, kitties.len());
+	assert!(kitties.iter().all(|kitty_id| *kitty_id < NextKittyId::<Test>::get()));
+	assert_eq!(
+		Gen0Minted::<Test>::get() + BredMinted::<Test>::get(),
+		NextKittyId::<Test>::get(),
+		"An id was minted twice or skipped"
+	);
+
+	// Only the stored kitties are listed.
+	assert!(ListForSale::<Test>::iter_keys().all(|kitty_id| Kitties::<Test>::contains_key(kitty_id)));
+
+	// The reserved balance of each account is the stake of its kitties.
+	for who in ACCOUNTS.iter() {
+		let owned = Owner::<Test>::iter().filter(|(_, owner)| owner == who).count() as u128;
+		assert_eq!(
+			Balances::reserved_balance(who),
+			owned * StakeForEachKitty::get(),
+			"The reserved balance of {} isn't the stake of {} kitties", who, owned
+		);
+	}
+}
@@ -0,0 +1,111 @@
+//! The gene layout of the kitty DNA, for everything that draws or describes a kitty.
+//!
+//! Each gene is a byte range of the 16-byte DNA. Breeding mixes the DNA of the parents bit
+//! by bit, so a child takes each gene from either parent or a mix of both.
+
+use sp_std::ops::Range;
+
+/// The RGB color of the body.
+pub const BODY_COLOR: Range<usize> = 0..3;
+/// The RGB color of the pattern.
+pub const PATTERN_COLOR: Range<usize> = 3..6;
+/// The RGB color of the eyes.
+pub const EYE_COLOR: Range<usize> = 6..9;
+/// The pattern on the body, see [`Pattern`].
+pub const PATTERN: usize = 9;
+/// The shape of the eyes, see [`EyeShape`].
+pub const EYE_SHAPE: usize = 10;
+/// The shape of the ears, see [`EarShape`].
+pub const EAR_SHAPE: usize = 11;
+/// The shape of the mouth, see [`MouthShape`].
+pub const MOUTH_SHAPE: usize = 12;
+/// The hue of the background, over 256 steps of the color wheel.
+pub const BACKGROUND_HUE: usize = 13;
+/// Not expressed yet, kept for future genes.
+pub const RESERVED: Range<usize> = 14..16;
+
+/// The pattern on the body.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pattern {
+	Plain,
+	Stripes,
+	Spots,
+	Patches,
+}
+
+/// The shape of the eyes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EyeShape {
+	Round,
+	Almond,
+	Sleepy,
+	Wide,
+}
+
+/// The shape of the ears.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EarShape {
+	Pointed,
+	Round,
+	Folded,
+}
+
+/// The shape of the mouth.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouthShape {
+	Smile,
+	Flat,
+	Open,
+}
+
+/// The traits expressed by a DNA.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Genes {
+	pub body_color: [u8; 3],
+	pub pattern_color: [u8; 3],
+	pub eye_color: [u8; 3],
+	pub pattern: Pattern,
+	pub eye_shape: EyeShape,
+	pub ear_shape: EarShape,
+	pub mouth_shape: MouthShape,
+	pub background_hue: u8,
+}
+
+impl Genes {
+	/// Read the genes of a DNA. Every DNA expresses some traits, the variants of a shape are
+	/// picked by the value of its byte modulo their number.
+	pub fn from_dna(dna: &[u8; 16]) -> Self {
+		Genes {
+			body_color: color(dna, BODY_COLOR),
+			pattern_color: color(dna, PATTERN_COLOR),
+			eye_color: color(dna, EYE_COLOR),
+			pattern: match dna[PATTERN] % 4 {
+				0 => Pattern::Plain,
+				1 => Pattern::Stripes,
+				2 => Pattern::Spots,
+				_ => Pattern::Patches,
+			},
+			eye_shape: match dna[EYE_SHAPE] % 4 {
+				0 => EyeShape::Round,
+				1 => EyeShape::Almond,
+				2 => EyeShape::Sleepy,
+				_ => EyeShape::Wide,
+			},
+			ear_shape: match dna[EAR_SHAPE] % 3 {
+				0 => EarShape::Pointed,
+				1 => EarShape::Round,
+				_ => EarShape::Folded,
+			},
+			mouth_shape: match dna[MOUTH_SHAPE] % 3 {
+				0 => MouthShape::Smile,
+				1 => MouthShape::Flat,
+				_ => MouthShape::Open,
+			},
+			background_hue: dna[BACKGROUND_HUE],
+		}
+	}
+}
+
+fn color(dna: &[u8; 16], gene: Range<usize>) -> [u8; 3] {
+	[dna[gene.start], dna[gene.start + 1], dna[gene.start + 2]]
+}
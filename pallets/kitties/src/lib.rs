@@ -1,9 +1,20 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub use pallet::*;
+pub use rate_limit::CheckKittyRateLimit;
 
-#[cfg(test)]
-mod mock;
+pub mod genes;
+pub mod metadata;
+pub mod rate_limit;
+pub mod traits;
+
+mod migrations;
+
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod mock;
+
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzzing;
 
 #[cfg(test)]
 mod tests;
@@ -16,28 +27,225 @@ pub mod pallet {
 	use frame_support::{
         dispatch::DispatchResult,
         pallet_prelude::*,
-        traits::{Randomness, Currency, ReservableCurrency}
+        traits::{Randomness, Currency, ReservableCurrency, LockIdentifier},
+        transactional,
     };
 	use frame_system::pallet_prelude::*;
     use codec::{Encode, Decode};
     use sp_io::hashing::blake2_128;
-    use sp_runtime::traits::{AtLeast32BitUnsigned, Bounded};
+    use sp_runtime::{
+        traits::{AtLeast32BitUnsigned, Bounded, IdentifyAccount, MaybeSerializeDeserialize, Saturating, Verify, Zero},
+        SaturatedConversion,
+    };
+    use sp_std::{prelude::*, convert::TryFrom};
+    use crate::traits::{KittyInspect, KittyLock, KittyTransfer};
 
     #[derive(Encode, Decode)]
     pub struct Kitty(pub [u8;16]);
+
+    impl Kitty {
+        /// The traits expressed by the DNA.
+        pub fn genes(&self) -> crate::genes::Genes {
+            crate::genes::Genes::from_dna(&self.0)
+        }
+    }
+
     type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
+    /// Bytes attached to a kitty (a name or metadata), with the deposit reserved for them.
+    /// The deposit is returned to the depositor, who may no longer own the kitty.
+    #[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
+    pub struct KittyAttachment<AccountId, Balance, Data> {
+        pub depositor: AccountId,
+        pub deposit: Balance,
+        pub data: Data,
+    }
+
+    /// The versions of the storage layout.
+    #[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+    pub enum Releases {
+        /// `KittiesCount` is both the next id and the total.
+        V1,
+        /// `KittiesCount` is split into `NextKittyId` and `TotalLiveKitties`.
+        V2,
+        /// `Kitties`, `Owner` and `ListForSale` store the values without Option.
+        V3,
+    }
+
+    impl Default for Releases {
+        fn default() -> Self {
+            Releases::V1
+        }
+    }
+
+    /// A sale of a kitty at the listed price.
+    #[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
+    pub struct SaleRecord<AccountId, Balance, BlockNumber> {
+        pub seller: AccountId,
+        pub buyer: AccountId,
+        pub price: Balance,
+        pub block: BlockNumber,
+    }
+
+    /// The parents and the generation of a kitty. A gen-0 kitty has no parents.
+    #[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
+    pub struct Lineage<KittyIndex> {
+        pub parents: Option<(KittyIndex, KittyIndex)>,
+        pub generation: u32,
+    }
+
+    impl<KittyIndex> Default for Lineage<KittyIndex> {
+        fn default() -> Self {
+            Lineage { parents: None, generation: 0 }
+        }
+    }
+
+    type SaleRecordOf<T> = SaleRecord<
+        <T as frame_system::Config>::AccountId,
+        BalanceOf<T>,
+        <T as frame_system::Config>::BlockNumber,
+    >;
+
+    /// The kinds of calls which can be paused by the admin.
+    #[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+    pub enum CallKind {
+        Create,
+        Transfer,
+        Breed,
+        Sell,
+        Buy,
+        Release,
+        Lend,
+        AcceptLoan,
+        SetName,
+        SetMetadata,
+    }
+
+    /// The phase of minting kitties by `create`.
+    #[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+    pub enum MintPhase {
+        /// No one can mint.
+        Closed,
+        /// Only the allowlisted accounts can mint, up to their quotas.
+        Allowlist,
+        /// Anyone can mint, up to the public cap and paying the mint price.
+        Public,
+    }
+
+    impl Default for MintPhase {
+        fn default() -> Self {
+            MintPhase::Public
+        }
+    }
+
+    /// A kitty offered on loan, waiting for the borrower to accept it and pay the fee.
+    #[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
+    pub struct LoanOffer<AccountId, Balance, BlockNumber> {
+        pub lender: AccountId,
+        pub borrower: AccountId,
+        pub duration: BlockNumber,
+        pub fee: Balance,
+    }
+
+    type LoanOfferOf<T> = LoanOffer<
+        <T as frame_system::Config>::AccountId,
+        BalanceOf<T>,
+        <T as frame_system::Config>::BlockNumber,
+    >;
+
+    /// A transfer of a kitty signed by its owner `from`, for a relayer to submit.
+    /// It's valid with the next transfer nonce of the owner, up to the block `expiry`.
+    #[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
+    pub struct TransferPayload<AccountId, KittyIndex, BlockNumber> {
+        pub from: AccountId,
+        pub to: AccountId,
+        pub kitty_id: KittyIndex,
+        pub nonce: u32,
+        pub expiry: BlockNumber,
+    }
+
+    pub type TransferPayloadOf<T> = TransferPayload<
+        <T as frame_system::Config>::AccountId,
+        <T as Config>::KittyIndex,
+        <T as frame_system::Config>::BlockNumber,
+    >;
+
+    /// The tag of the signed transfers, for a signature of another message not to pass for one.
+    pub const TRANSFER_SIGNING_TAG: &[u8] = b"kitties:transfer";
+
+    /// A voucher of the voucher issuer, for `who` to claim a starter kitty up to the block `expiry`.
+    #[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
+    pub struct StarterVoucher<AccountId, BlockNumber> {
+        pub who: AccountId,
+        pub expiry: BlockNumber,
+    }
+
+    pub type StarterVoucherOf<T> = StarterVoucher<
+        <T as frame_system::Config>::AccountId,
+        <T as frame_system::Config>::BlockNumber,
+    >;
+
+    type KittyNameOf<T> = KittyAttachment<
+        <T as frame_system::Config>::AccountId,
+        BalanceOf<T>,
+        BoundedVec<u8, <T as Config>::MaxNameLength>,
+    >;
+    type KittyMetadataOf<T> = KittyAttachment<
+        <T as frame_system::Config>::AccountId,
+        BalanceOf<T>,
+        BoundedVec<u8, <T as Config>::MaxMetadataLength>,
+    >;
+
     #[pallet::config]
 	pub trait Config: frame_system::Config {
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
         type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
         // Define KittyIndex in Runtime.
-        type KittyIndex: Parameter + AtLeast32BitUnsigned + Default + Copy + Bounded;
+        type KittyIndex: Parameter + AtLeast32BitUnsigned + Default + Copy + Bounded + MaybeSerializeDeserialize;
         type Currency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
         // Configurable constant for the amount of staking when create a kitty,
         // to avoid the user create a big number of kitties to attract the chain.
         #[pallet::constant]
         type StakeForEachKitty: Get<BalanceOf<Self>>;
+        // The amount reserved for each byte of a kitty name or metadata.
+        #[pallet::constant]
+        type DepositPerByte: Get<BalanceOf<Self>>;
+        // Maximum bytes of a kitty name.
+        #[pallet::constant]
+        type MaxNameLength: Get<u32>;
+        // Maximum bytes of kitty metadata, e.g. a URI.
+        #[pallet::constant]
+        type MaxMetadataLength: Get<u32>;
+        // Maximum number of loans which expire at the same block.
+        #[pallet::constant]
+        type MaxLoansPerBlock: Get<u32>;
+        // The origin which can intervene, e.g. to respond to exploits.
+        type AdminOrigin: EnsureOrigin<Self::Origin>;
+        // The account receiving the mint price in the public phase.
+        type Treasury: Get<Self::AccountId>;
+        // Maximum number of gen-0 kitties, which are minted by create.
+        #[pallet::constant]
+        type MaxGen0Supply: Get<u32>;
+        // Maximum number of all kitties, the gen-0 and the bred ones.
+        #[pallet::constant]
+        type MaxSupply: Get<u32>;
+        // Maximum number of recent sales kept for each kitty.
+        #[pallet::constant]
+        type MaxSalesPerKitty: Get<u32>;
+        // Maximum number of recent sales kept for all kitties.
+        #[pallet::constant]
+        type MaxRecentSales: Get<u32>;
+        // The signature of the transfer payloads signed by the owners of kitties.
+        type OffchainSignature: Verify<Signer = Self::SigningPublicKey> + Parameter;
+        // The public key of the signatures, identifying the account of the owner.
+        type SigningPublicKey: IdentifyAccount<AccountId = Self::AccountId>;
+        // Maximum number of kitties created or bred by an account in an era,
+        // checked by the `CheckKittyRateLimit` signed extension.
+        #[pallet::constant]
+        type MaxMintsPerEra: Get<u32>;
+        // The number of blocks in an era of the mint rate limit.
+        #[pallet::constant]
+        type MintEraLength: Get<Self::BlockNumber>;
 	}
 
 	#[pallet::pallet]
@@ -48,31 +256,222 @@ pub mod pallet {
 	#[pallet::metadata(T::AccountId = "AccountId")]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-        KittyCreated(T::AccountId, T::KittyIndex),
+        /// A gen-0 kitty was created.
+        KittyCreated { owner: T::AccountId, kitty_id: T::KittyIndex, dna: [u8; 16] },
+        /// A kitty was bred from 2 parents.
+        KittyBred {
+            owner: T::AccountId,
+            child: T::KittyIndex,
+            parent1: T::KittyIndex,
+            parent2: T::KittyIndex,
+            dna: [u8; 16],
+        },
         KittyTransferred(T::AccountId, T::AccountId, T::KittyIndex),
-        KittyListed(T::AccountId, T::KittyIndex, Option<BalanceOf<T>>),
+        /// A kitty was listed for sale. [owner, kitty_id, price]
+        KittyListed(T::AccountId, T::KittyIndex, BalanceOf<T>),
+        /// The listing of a kitty was cleared, by the owner, a sale, a transfer or a removal.
+        KittyDelisted { owner: T::AccountId, kitty_id: T::KittyIndex },
+        /// A kitty name was set or cleared. [who, kitty_id]
+        KittyNameSet(T::AccountId, T::KittyIndex),
+        /// Kitty metadata was set or cleared. [who, kitty_id]
+        KittyMetadataSet(T::AccountId, T::KittyIndex),
+        /// The name and metadata of a kitty were removed by root. [kitty_id]
+        KittyMetadataCleared(T::KittyIndex),
+        /// A kitty was offered on loan. [lender, borrower, kitty_id, duration, fee]
+        KittyLoanOffered(T::AccountId, T::AccountId, T::KittyIndex, T::BlockNumber, BalanceOf<T>),
+        /// A kitty was lent until the block. [lender, borrower, kitty_id, expiry]
+        KittyLent(T::AccountId, T::AccountId, T::KittyIndex, T::BlockNumber),
+        /// A lent kitty was returned to its owner. [owner, kitty_id]
+        KittyReturned(T::AccountId, T::KittyIndex),
+        /// A kitty was released by its owner and the stake was returned. [owner, kitty_id]
+        KittyReleased(T::AccountId, T::KittyIndex),
+        /// A kitty was locked by another pallet. [kitty_id, reason]
+        KittyLocked(T::KittyIndex, LockIdentifier),
+        /// A kitty was unlocked by another pallet. [kitty_id, reason]
+        KittyUnlocked(T::KittyIndex, LockIdentifier),
+        /// A kitty was sold at the listed price. [seller, buyer, kitty_id, price]
+        KittySold(T::AccountId, T::AccountId, T::KittyIndex, BalanceOf<T>),
+        /// A kitty was burned by the admin and the stake was returned. [owner, kitty_id]
+        KittyBurned(T::AccountId, T::KittyIndex),
+        /// The marketplace was paused by the admin.
+        MarketplacePaused,
+        /// The marketplace was unpaused by the admin.
+        MarketplaceUnpaused,
+        /// A kind of calls was paused or unpaused by the admin. [call, paused]
+        CallPaused(CallKind, bool),
+        /// The mint phase was set by the admin. [phase]
+        MintPhaseSet(MintPhase),
+        /// The mint quota of an account in the allowlist phase was set by the admin. [who, quota]
+        MintAllowlistSet(T::AccountId, u32),
+        /// The cap and the price of the public phase were set by the admin. [cap, price]
+        PublicMintSet(Option<u32>, BalanceOf<T>),
+        /// The stake for each kitty was set by the admin. [stake]
+        StakeAmountSet(BalanceOf<T>),
+        /// The issuer of the starter kitty vouchers was set by the admin. [issuer]
+        VoucherIssuerSet(Option<T::AccountId>),
 	}
 
-    /// Storage for tracking all the kitties
+    /// Storage for the id of the next kitty. The ids of the removed kitties are not reused.
+    #[pallet::storage]
+	#[pallet::getter(fn next_kitty_id)]
+	pub type NextKittyId<T: Config> = StorageValue<_, T::KittyIndex, ValueQuery>;
+
+    /// Storage for the number of kitties which are not removed.
     #[pallet::storage]
-	#[pallet::getter(fn kitties_count)]
-	pub type KittiesCount<T: Config> = StorageValue<_, T::KittyIndex>;
+	#[pallet::getter(fn total_live_kitties)]
+	pub type TotalLiveKitties<T: Config> = StorageValue<_, T::KittyIndex, ValueQuery>;
+
+    /// Storage for the version of the storage layout, for the migrations.
+    #[pallet::storage]
+	pub(crate) type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
 
     /// Storage for every kitty.
     #[pallet::storage]
 	#[pallet::getter(fn kitties)]
-	pub type Kitties<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, Option<Kitty>, ValueQuery>;
+	pub type Kitties<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, Kitty>;
 
-    /// Storage for kitties which are listed for sale.
-    /// If the list price (Option<BalanceOf<T>>) is None, means the specific kitty is not for sale.
+    /// Storage for kitties which are listed for sale, with the list price.
+    /// If there's no entry, means the specific kitty is not for sale.
     #[pallet::storage]
 	#[pallet::getter(fn kitties_list_for_sales)]
-	pub type ListForSale<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, Option<BalanceOf<T>>, ValueQuery>;
+	pub type ListForSale<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, BalanceOf<T>>;
 
     /// Storage for tracking the ownership of kitties.
     #[pallet::storage]
 	#[pallet::getter(fn owner)]
-	pub type Owner<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, Option<T::AccountId>, ValueQuery>;
+	pub type Owner<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, T::AccountId>;
+
+    /// Storage for the stake reserved from the owner of each kitty.
+    /// The kitties created before the stake could be changed have no entry, and were staked
+    /// with `StakeForEachKitty`.
+    #[pallet::storage]
+    pub type KittyStake<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, BalanceOf<T>>;
+
+    /// Storage for the stake set by the admin. If None, `StakeForEachKitty` is used.
+    #[pallet::storage]
+    pub type StakeAmount<T: Config> = StorageValue<_, BalanceOf<T>>;
+
+    /// Storage for the pause of the marketplace by the admin, when selling and buying are disabled.
+    #[pallet::storage]
+    #[pallet::getter(fn marketplace_paused)]
+    pub type IsMarketplacePaused<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// Storage for the kinds of calls paused by the admin.
+    #[pallet::storage]
+    pub type PausedCalls<T: Config> = StorageMap<_, Twox64Concat, CallKind, ()>;
+
+    /// Storage for the mint phase set by the admin.
+    #[pallet::storage]
+    #[pallet::getter(fn mint_phase)]
+    pub type CurrentMintPhase<T: Config> = StorageValue<_, MintPhase, ValueQuery>;
+
+    /// Storage for the remaining mint quotas of the accounts in the allowlist phase.
+    #[pallet::storage]
+    #[pallet::getter(fn mint_allowlist)]
+    pub type MintAllowlist<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    /// Storage for the maximum number of kitties minted in the public phase. If None, no cap.
+    #[pallet::storage]
+    #[pallet::getter(fn public_mint_cap)]
+    pub type PublicMintCap<T: Config> = StorageValue<_, u32>;
+
+    /// Storage for the number of kitties minted in the public phase.
+    #[pallet::storage]
+    #[pallet::getter(fn public_minted)]
+    pub type PublicMinted<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Storage for the price paid to the treasury for minting in the public phase.
+    #[pallet::storage]
+    #[pallet::getter(fn mint_price)]
+    pub type MintPrice<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+    /// Storage for the number of gen-0 kitties ever minted.
+    #[pallet::storage]
+    #[pallet::getter(fn gen0_minted)]
+    pub type Gen0Minted<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Storage for the number of kitties ever bred.
+    #[pallet::storage]
+    #[pallet::getter(fn bred_minted)]
+    pub type BredMinted<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Storage for the recent sales of each kitty, from the oldest to the latest.
+    #[pallet::storage]
+    #[pallet::getter(fn kitty_sales)]
+    pub type KittySales<T: Config> = StorageMap<
+        _, Blake2_128Concat, T::KittyIndex, BoundedVec<SaleRecordOf<T>, T::MaxSalesPerKitty>, ValueQuery
+    >;
+
+    /// Storage for the recent sales of all kitties, from the oldest to the latest.
+    #[pallet::storage]
+    #[pallet::getter(fn recent_sales)]
+    pub type RecentSales<T: Config> = StorageValue<
+        _, BoundedVec<(T::KittyIndex, SaleRecordOf<T>), T::MaxRecentSales>, ValueQuery
+    >;
+
+    /// Storage for the account signing the vouchers of the starter kitties.
+    /// If None, no starter kitty can be claimed.
+    #[pallet::storage]
+    #[pallet::getter(fn voucher_issuer)]
+    pub type VoucherIssuer<T: Config> = StorageValue<_, T::AccountId>;
+
+    /// Storage for the accounts which claimed their starter kitty.
+    #[pallet::storage]
+    pub type StarterKittyClaimed<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, ()>;
+
+    /// Storage for the kitties created or bred by each account in its latest era: (era, count).
+    #[pallet::storage]
+    pub type MintsInEra<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, (T::BlockNumber, u32), ValueQuery>;
+
+    /// Storage for the next nonce of the signed transfers of each account.
+    #[pallet::storage]
+    #[pallet::getter(fn transfer_nonce)]
+    pub type TransferNonces<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    /// Storage for the lineage of the kitties. The gen-0 kitties have the default lineage.
+    /// The lineage of the kitties minted before it was recorded, or imported at genesis, is
+    /// unknown, and so is the lineage of the kitties bred from them.
+    #[pallet::storage]
+    #[pallet::getter(fn lineage)]
+    pub type KittyLineage<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, Lineage<T::KittyIndex>>;
+
+    /// Storage for the user of a lent kitty. The owner in `Owner` keeps the ownership,
+    /// while the user can breed with the kitty until the loan expires.
+    #[pallet::storage]
+    #[pallet::getter(fn kitty_user)]
+    pub type KittyUser<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, T::AccountId>;
+
+    /// Storage for the loans offered and not yet accepted.
+    #[pallet::storage]
+    #[pallet::getter(fn loan_offer)]
+    pub type LoanOffers<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, LoanOfferOf<T>>;
+
+    /// Storage for the kitties to be returned at each block, processed in `on_initialize`.
+    #[pallet::storage]
+    #[pallet::getter(fn loan_expiries)]
+    pub type LoanExpiries<T: Config> = StorageMap<
+        _, Twox64Concat, T::BlockNumber, BoundedVec<T::KittyIndex, T::MaxLoansPerBlock>, ValueQuery
+    >;
+
+    /// Storage for the locks held by other pallets on kitties, see `KittyLock`.
+    #[pallet::storage]
+    pub type Locks<T: Config> = StorageDoubleMap<
+        _, Blake2_128Concat, T::KittyIndex, Blake2_128Concat, LockIdentifier, ()
+    >;
+
+    /// Storage for the exclusive locks, which are the only locks on their kitties.
+    #[pallet::storage]
+    pub type ExclusiveLocks<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, LockIdentifier>;
+
+    /// Storage for the names of kitties.
+    #[pallet::storage]
+    #[pallet::getter(fn kitty_name)]
+    pub type KittyNames<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, KittyNameOf<T>>;
+
+    /// Storage for the metadata (e.g. a URI) of kitties.
+    #[pallet::storage]
+    #[pallet::getter(fn kitty_metadata)]
+    pub type KittyMetadata<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, KittyMetadataOf<T>>;
 
 	#[pallet::error]
 	pub enum Error<T> {
@@ -84,17 +483,155 @@ pub mod pallet {
         NotForSale,
         NotEnoughBalanceForStaking,
         NotEnoughBalanceForBuying,
+        NotEnoughBalanceForDeposit,
+        NameTooLong,
+        MetadataTooLong,
+        KittyOnLoan,
+        NotKittyUser,
+        NoLoanOffer,
+        InvalidLoanDuration,
+        TooManyLoansExpiring,
+        KittyLocked,
+        AlreadyLocked,
+        NotLocked,
+        MarketplaceIsPaused,
+        CallIsPaused,
+        MintClosed,
+        NotInMintAllowlist,
+        PublicMintCapReached,
+        Gen0SupplyExhausted,
+        SupplyExhausted,
+        InvalidSignature,
+        InvalidTransferNonce,
+        TransferPayloadExpired,
+        NoVoucherIssuer,
+        InvalidVoucher,
+        VoucherExpired,
+        StarterKittyAlreadyClaimed,
+        InvalidStakeAmount,
+        BorrowerIsOwner,
 	}
 
+    #[pallet::genesis_config]
+    pub struct GenesisConfig<T: Config> {
+        /// The kitties at genesis, e.g. imported from another chain:
+        /// (kitty_id, owner, dna, price, lineage), the lineage as (parents, generation) if it's known.
+        pub kitties: Vec<(
+            T::KittyIndex,
+            T::AccountId,
+            [u8; 16],
+            Option<BalanceOf<T>>,
+            Option<(Option<(T::KittyIndex, T::KittyIndex)>, u32)>,
+        )>,
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Config> Default for GenesisConfig<T> {
+        fn default() -> Self {
+            GenesisConfig { kitties: Vec::new() }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+        fn build(&self) {
+            let mut gen0 = 0u32;
+            for (kitty_id, owner, dna, price, lineage) in self.kitties.iter() {
+                assert!(!Kitties::<T>::contains_key(kitty_id), "Duplicate kitty in genesis");
+                // The owners who can't pay the stake hold their kitties without stake.
+                let stake = Pallet::<T>::stake_amount();
+                let stake = if T::Currency::reserve(owner, stake).is_ok() { stake } else { Zero::zero() };
+
+                KittyStake::<T>::insert(kitty_id, stake);
+                Kitties::<T>::insert(kitty_id, Kitty(*dna));
+                Owner::<T>::insert(kitty_id, owner.clone());
+                if let Some(price) = price {
+                    ListForSale::<T>::insert(kitty_id, price);
+                }
+                if let Some((parents, generation)) = lineage {
+                    KittyLineage::<T>::insert(kitty_id, Lineage { parents: *parents, generation: *generation });
+                    if *generation == 0 {
+                        gen0 += 1;
+                    }
+                }
+                if *kitty_id >= NextKittyId::<T>::get() {
+                    NextKittyId::<T>::put(kitty_id.saturating_add(1u32.into()));
+                }
+            }
+            // Only the kitties known to be gen-0 count as gen-0, the others as bred. The chain
+            // they are imported from may have more gen-0 kitties than MaxGen0Supply: no more
+            // gen-0 kitties are minted then.
+            let count = self.kitties.len() as u32;
+            assert!(count <= T::MaxSupply::get(), "More kitties in genesis than MaxSupply");
+            TotalLiveKitties::<T>::put(T::KittyIndex::from(count));
+            Gen0Minted::<T>::put(gen0);
+            BredMinted::<T>::put(count - gen0);
+
+            // A new chain starts with the latest storage layout, so no migration runs on it.
+            StorageVersion::<T>::put(Releases::V3);
+        }
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_runtime_upgrade() -> Weight {
+            crate::migrations::migrate_to_v2::<T>()
+                .saturating_add(crate::migrations::migrate_to_v3::<T>())
+        }
+
+        /// Check the storage after the migrations, with try-runtime.
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade() -> Result<(), &'static str> {
+            Self::do_try_state()
+        }
+
+        /// Check the constants of the config, in the integrity test of the runtime.
+        fn integrity_test() {
+            let stake = T::StakeForEachKitty::get();
+            assert!(!stake.is_zero(), "StakeForEachKitty must be greater than zero");
+            assert!(
+                stake >= T::Currency::minimum_balance(),
+                "StakeForEachKitty must be at least the existential deposit"
+            );
+            assert!(T::MaxGen0Supply::get() <= T::MaxSupply::get(), "MaxGen0Supply must be at most MaxSupply");
+            assert!(!T::MintEraLength::get().is_zero(), "MintEraLength must be greater than zero");
+        }
+
+        /// Return the kitties whose loans expire at this block to their owners.
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            let expired = LoanExpiries::<T>::take(n);
+            let count = expired.len() as Weight;
+
+            for kitty_id in expired.into_iter() {
+                KittyUser::<T>::remove(kitty_id);
+                if let Some(owner) = Owner::<T>::get(kitty_id) {
+                    Self::deposit_event(Event::KittyReturned(owner, kitty_id));
+                }
+            }
+
+            T::DbWeight::get().reads_writes(1 + count, 1 + count)
+        }
+
+        /// Cache the metadata JSON of the kitties created since the last run,
+        /// see `crate::metadata`.
+        fn offchain_worker(_n: T::BlockNumber) {
+            crate::metadata::cache_new_metadata::<T>();
+        }
+    }
+
 	#[pallet::call]
 	impl<T:Config> Pallet<T> {
 
         /// Create a kitty with the stake configurated from:
         /// #[pallet::constant]
         ///      type StakeForEachKitty: Get<BalanceOf<Self>>)
+        /// The mint phase, the allowlist quotas, the public cap and the mint price apply.
         #[pallet::weight(1_000)]
+        #[transactional]
         pub fn create(origin: OriginFor<T>) -> DispatchResult{
             let who = ensure_signed(origin)?;
+            Self::ensure_call_enabled(CallKind::Create)?;
+            Self::check_mint(&who)?;
 
             let dna = Self::random_value(&who);
 
@@ -116,7 +653,10 @@ pub mod pallet {
             //
             // Self::deposit_event(Event::KittyCreated(who, kitty_id));
             // ----------
-            Self::new_kitty_with_stake(&who, dna)?;
+            let kitty_id = Self::new_kitty_with_stake(&who, dna, true)?;
+            Self::note_mint(&who);
+
+            Self::deposit_event(Event::KittyCreated { owner: who, kitty_id, dna });
 
             Ok(())
         }
@@ -125,33 +665,57 @@ pub mod pallet {
         #[pallet::weight(1_000)]
         pub fn transfer(origin: OriginFor<T>, new_owner: T::AccountId, kitty_id: T::KittyIndex) -> DispatchResult {
             let who = ensure_signed(origin)?;
-            // Ensure transfer only from the OWNER of kitties.
-            ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotOwner);
+            Self::ensure_call_enabled(CallKind::Transfer)?;
 
-            let stake_amount = T::StakeForEachKitty::get();
+            Self::transfer_kitty(&who, &new_owner, kitty_id)
+        }
 
-            // Staking from new owner and unstaking from the ex-ownder
-            T::Currency::reserve(&new_owner, stake_amount)
-                .map_err(|_| Error::<T>::NotEnoughBalanceForStaking)?;
-            T::Currency::unreserve(&who, stake_amount);
+        /// Transfer a kitty with a payload signed by its owner, for the players without the
+        /// native token: the relayer submitting it pays the fee, the new owner the stake.
+        /// The owner signs the message of `transfer_signing_message`.
+        #[pallet::weight(1_000)]
+        pub fn transfer_with_signature(
+            origin: OriginFor<T>,
+            payload: TransferPayloadOf<T>,
+            signature: T::OffchainSignature,
+        ) -> DispatchResult {
+            ensure_signed(origin)?;
+            Self::ensure_call_enabled(CallKind::Transfer)?;
+            ensure!(
+                frame_system::Pallet::<T>::block_number() <= payload.expiry,
+                Error::<T>::TransferPayloadExpired
+            );
+            // The nonce protects from replaying the payload.
+            let nonce = Self::transfer_nonce(&payload.from);
+            ensure!(payload.nonce == nonce, Error::<T>::InvalidTransferNonce);
+            ensure!(
+                signature.verify(&Self::transfer_signing_message(&payload)[..], &payload.from),
+                Error::<T>::InvalidSignature
+            );
 
-            // Update storage.
-            Owner::<T>::insert(kitty_id, Some(new_owner.clone()));
-            // Emit the event.
-            Self::deposit_event(Event::KittyTransferred(who, new_owner, kitty_id));
+            Self::transfer_kitty(&payload.from, &payload.to, payload.kitty_id)?;
+            TransferNonces::<T>::insert(&payload.from, nonce.saturating_add(1));
 
             Ok(())
         }
 
-        /// Breed a kitty from other 2 kitties (Allow the kitty parents belong to other owners).
+        /// Breed a kitty from other 2 kitties, owned by the breeder or lent to it.
         #[pallet::weight(1_000)]
         pub fn breed(origin: OriginFor<T>, kitty_id_1: T::KittyIndex, kitty_id_2: T::KittyIndex) -> DispatchResult {
             let who = ensure_signed(origin)?;
+            Self::ensure_call_enabled(CallKind::Breed)?;
             // Ensure the parents are not same.
             ensure!(kitty_id_1 != kitty_id_2, Error::<T>::SameParentIndex);
             // Ensure there're the parents in the Storage.
             let kitty1 = Self::kitties(kitty_id_1).ok_or(Error::<T>::InvalidKittyIndex)?;
             let kitty2 = Self::kitties(kitty_id_2).ok_or(Error::<T>::InvalidKittyIndex)?;
+            // A kitty is bred by its owner, or only by its user while it's lent.
+            for kitty_id in [kitty_id_1, kitty_id_2].iter() {
+                match Self::kitty_user(kitty_id) {
+                    Some(user) => ensure!(user == who, Error::<T>::NotKittyUser),
+                    None => ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotOwner),
+                }
+            }
             // Breed new kitty from the parents.
             let dna_1 = kitty1.0;
             let dna_2 = kitty2.0;
@@ -175,7 +739,20 @@ pub mod pallet {
             // KittiesCount::<T>::put(kitty_id + 1u32.into());
             // Self::deposit_event(Event::KittyCreated(who, kitty_id));
             // ----------
-            Self::new_kitty_with_stake(&who, new_dna)?;
+            let child = Self::new_kitty_with_stake(&who, new_dna, false)?;
+            Self::note_mint(&who);
+            if let (Some(lineage_1), Some(lineage_2)) = (Self::lineage(kitty_id_1), Self::lineage(kitty_id_2)) {
+                let generation = lineage_1.generation.max(lineage_2.generation).saturating_add(1);
+                KittyLineage::<T>::insert(child, Lineage { parents: Some((kitty_id_1, kitty_id_2)), generation });
+            }
+
+            Self::deposit_event(Event::KittyBred {
+                owner: who,
+                child,
+                parent1: kitty_id_1,
+                parent2: kitty_id_2,
+                dna: new_dna,
+            });
 
             Ok(())
         }
@@ -184,50 +761,534 @@ pub mod pallet {
         #[pallet::weight(1_000)]
         pub fn sell(origin: OriginFor<T>, kitty_id: T::KittyIndex, price: Option<BalanceOf<T>>) -> DispatchResult {
             let who = ensure_signed(origin)?;
+            Self::ensure_call_enabled(CallKind::Sell)?;
             // Ensure only the kitty owner can sell it.
             ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotOwner);
+            Self::ensure_transferable(kitty_id)?;
+            ensure!(!Self::marketplace_paused(), Error::<T>::MarketplaceIsPaused);
             // Set a price. If the price is None, it means the kitty is not for sale.
-            ListForSale::<T>::mutate_exists(kitty_id, |p| *p = Some(price));
-            // Emit event.
-            Self::deposit_event(Event::KittyListed(who, kitty_id, price));
+            match price {
+                Some(price) => {
+                    ListForSale::<T>::insert(kitty_id, price);
+                    Self::deposit_event(Event::KittyListed(who, kitty_id, price));
+                },
+                None => Self::delist(&who, kitty_id),
+            }
 
             Ok(())
         }
 
         /// Buy a kitty from its owner.
         #[pallet::weight(1_000)]
+        #[transactional]
         pub fn buy(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
             let buyer = ensure_signed(origin)?;
-            let owner = Owner::<T>::get(kitty_id).unwrap();
+            Self::ensure_call_enabled(CallKind::Buy)?;
+            ensure!(!Self::marketplace_paused(), Error::<T>::MarketplaceIsPaused);
+            let owner = Owner::<T>::get(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
             // Ensure the buyer is not the owner.
             ensure!(Some(buyer.clone()) != Some(owner.clone()), Error::<T>::BuyerIsOwner);
-            // If the price in the ListForSale is None, the kitty is not for sale.
+            // If there's no price in the ListForSale, the kitty is not for sale.
             let amount = ListForSale::<T>::get(kitty_id).ok_or(Error::<T>::NotForSale)?;
+            Self::ensure_transferable(kitty_id)?;
             // Check the buyer with enough balance to buy. Ensure the free balance can pay and stake also.
             let buyer_balance = T::Currency::free_balance(&buyer);
-            let stake_amount = T::StakeForEachKitty::get();
+            let stake_amount = Self::stake_amount();
             ensure!(buyer_balance > (amount + stake_amount), Error::<T>::NotEnoughBalanceForBuying);
             // Staking for own the kitty.
             T::Currency::reserve(&buyer, stake_amount)
                 .map_err(|_| Error::<T>::NotEnoughBalanceForStaking)?;
             // Unstaking from the ex-ownder (the seller).
-			T::Currency::unreserve(&owner, stake_amount);
+			T::Currency::unreserve(&owner, Self::kitty_stake(kitty_id));
+            KittyStake::<T>::insert(kitty_id, stake_amount);
             // Transfer the price from buyer to the seller.
 			T::Currency::transfer(&buyer, &owner, amount, frame_support::traits::ExistenceRequirement::KeepAlive)?;
-            // Remove from the List.
-			ListForSale::<T>::remove(kitty_id);
+            // Remove from the List, and the loan offer of the seller.
+            Self::delist(&owner, kitty_id);
+            LoanOffers::<T>::remove(kitty_id);
             // Update the storage with the new owner.
-            Owner::<T>::insert(kitty_id, Some(buyer.clone()));
+            Owner::<T>::insert(kitty_id, buyer.clone());
+            // Record the sale.
+            let record = SaleRecord {
+                seller: owner.clone(),
+                buyer: buyer.clone(),
+                price: amount,
+                block: frame_system::Pallet::<T>::block_number(),
+            };
+            KittySales::<T>::mutate(kitty_id, |sales| Self::push_sale(sales, record.clone()));
+            RecentSales::<T>::mutate(|sales| Self::push_sale(sales, (kitty_id, record)));
             // Emit the event.
-            Self::deposit_event(Event::KittyTransferred(owner, buyer, kitty_id));
+            Self::deposit_event(Event::KittySold(owner, buyer, kitty_id, amount));
+
+            Ok(())
+        }
+
+        /// Release a kitty by its owner. The kitty is removed and the stake is returned.
+        #[pallet::weight(1_000)]
+        pub fn release(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_call_enabled(CallKind::Release)?;
+            // Ensure only the kitty owner can release it.
+            ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotOwner);
+            Self::ensure_transferable(kitty_id)?;
+
+            Self::remove_kitty(&who, kitty_id);
+
+            Self::deposit_event(Event::KittyReleased(who, kitty_id));
+
+            Ok(())
+        }
+
+        /// Offer a kitty on loan to `borrower` for `duration` blocks with a `fee`.
+        /// The loan starts when the borrower accepts it with accept_loan().
+        #[pallet::weight(1_000)]
+        pub fn lend(
+            origin: OriginFor<T>,
+            kitty_id: T::KittyIndex,
+            borrower: T::AccountId,
+            duration: T::BlockNumber,
+            fee: BalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_call_enabled(CallKind::Lend)?;
+            // Ensure only the kitty owner can lend it.
+            ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotOwner);
+            ensure!(borrower != who, Error::<T>::BorrowerIsOwner);
+            Self::ensure_transferable(kitty_id)?;
+            ensure!(!duration.is_zero(), Error::<T>::InvalidLoanDuration);
+
+            LoanOffers::<T>::insert(kitty_id, LoanOffer { lender: who.clone(), borrower: borrower.clone(), duration, fee });
+
+            Self::deposit_event(Event::KittyLoanOffered(who, borrower, kitty_id, duration, fee));
+
+            Ok(())
+        }
+
+        /// Accept a loan offer, paying the fee to the lender.
+        /// The kitty is returned automatically when the loan expires.
+        #[pallet::weight(1_000)]
+        #[transactional]
+        pub fn accept_loan(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_call_enabled(CallKind::AcceptLoan)?;
+            let offer = LoanOffers::<T>::get(kitty_id)
+                .filter(|o| o.borrower == who)
+                .ok_or(Error::<T>::NoLoanOffer)?;
+            // The offer is outdated if the kitty changed hands or was lent to others.
+            ensure!(Some(offer.lender.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NoLoanOffer);
+            Self::ensure_transferable(kitty_id)?;
+
+            let expiry = frame_system::Pallet::<T>::block_number().saturating_add(offer.duration);
+            LoanExpiries::<T>::try_mutate(expiry, |ids| ids.try_push(kitty_id))
+                .map_err(|_| Error::<T>::TooManyLoansExpiring)?;
+            // Pay the fee to the lender.
+            T::Currency::transfer(&who, &offer.lender, offer.fee, frame_support::traits::ExistenceRequirement::KeepAlive)?;
+
+            LoanOffers::<T>::remove(kitty_id);
+            KittyUser::<T>::insert(kitty_id, who.clone());
+
+            Self::deposit_event(Event::KittyLent(offer.lender, who, kitty_id, expiry));
+
+            Ok(())
+        }
+
+        /// Set the name of a kitty, reserving `DepositPerByte` for each byte.
+        /// An empty name clears it and returns the deposit.
+        #[pallet::weight(1_000)]
+        pub fn set_kitty_name(origin: OriginFor<T>, kitty_id: T::KittyIndex, name: Vec<u8>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_call_enabled(CallKind::SetName)?;
+            // Ensure only the kitty owner can name it.
+            ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotOwner);
+            let name = BoundedVec::<u8, T::MaxNameLength>::try_from(name)
+                .map_err(|_| Error::<T>::NameTooLong)?;
+
+            KittyNames::<T>::try_mutate_exists(kitty_id, |n| -> DispatchResult {
+                let old = n.take().map(|n| (n.depositor, n.deposit));
+                *n = Self::attach(&who, old, name)?;
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::KittyNameSet(who, kitty_id));
+
+            Ok(())
+        }
+
+        /// Set the metadata of a kitty, reserving `DepositPerByte` for each byte.
+        /// Empty metadata clears it and returns the deposit.
+        #[pallet::weight(1_000)]
+        pub fn set_kitty_metadata(origin: OriginFor<T>, kitty_id: T::KittyIndex, metadata: Vec<u8>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_call_enabled(CallKind::SetMetadata)?;
+            // Ensure only the kitty owner can set its metadata.
+            ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotOwner);
+            let metadata = BoundedVec::<u8, T::MaxMetadataLength>::try_from(metadata)
+                .map_err(|_| Error::<T>::MetadataTooLong)?;
+
+            KittyMetadata::<T>::try_mutate_exists(kitty_id, |m| -> DispatchResult {
+                let old = m.take().map(|m| (m.depositor, m.deposit));
+                *m = Self::attach(&who, old, metadata)?;
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::KittyMetadataSet(who, kitty_id));
+
+            Ok(())
+        }
+
+        /// Remove the name and metadata of a kitty for moderation. The deposits are slashed.
+        #[pallet::weight(1_000)]
+        pub fn force_clear_metadata(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(Kitties::<T>::contains_key(kitty_id), Error::<T>::InvalidKittyIndex);
+
+            if let Some(name) = KittyNames::<T>::take(kitty_id) {
+                let _ = T::Currency::slash_reserved(&name.depositor, name.deposit);
+            }
+            if let Some(metadata) = KittyMetadata::<T>::take(kitty_id) {
+                let _ = T::Currency::slash_reserved(&metadata.depositor, metadata.deposit);
+            }
+
+            Self::deposit_event(Event::KittyMetadataCleared(kitty_id));
+
+            Ok(())
+        }
+
+        /// Transfer a kitty by the admin, from its owner to `new_owner` who stakes for it.
+        /// Like `force_burn`, it fails on the kitties which are locked or lent out, as the
+        /// pallets holding the locks settle with the owner, e.g. redeem the vault shares first.
+        /// The stake of the owner is returned, and the new owner stakes only if it can pay.
+        #[pallet::weight(1_000)]
+        pub fn force_transfer(origin: OriginFor<T>, new_owner: T::AccountId, kitty_id: T::KittyIndex) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            let owner = Owner::<T>::get(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+            Self::ensure_transferable(kitty_id)?;
+
+            T::Currency::unreserve(&owner, Self::kitty_stake(kitty_id));
+            let stake = Self::stake_amount();
+            let stake = if T::Currency::reserve(&new_owner, stake).is_ok() { stake } else { Zero::zero() };
+
+            KittyStake::<T>::insert(kitty_id, stake);
+            Owner::<T>::insert(kitty_id, new_owner.clone());
+            // The loan offer and the listing of the ex-owner are removed.
+            LoanOffers::<T>::remove(kitty_id);
+            Self::delist(&owner, kitty_id);
+
+            Self::deposit_event(Event::KittyTransferred(owner, new_owner, kitty_id));
+
+            Ok(())
+        }
+
+        /// Burn a kitty by the admin. The stake and deposits are returned.
+        #[pallet::weight(1_000)]
+        pub fn force_burn(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            let owner = Owner::<T>::get(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+            Self::ensure_transferable(kitty_id)?;
+
+            Self::remove_kitty(&owner, kitty_id);
+
+            Self::deposit_event(Event::KittyBurned(owner, kitty_id));
+
+            Ok(())
+        }
+
+        /// Pause the marketplace by the admin, selling and buying are disabled.
+        #[pallet::weight(1_000)]
+        pub fn pause_marketplace(origin: OriginFor<T>) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            IsMarketplacePaused::<T>::put(true);
+
+            Self::deposit_event(Event::MarketplacePaused);
+
+            Ok(())
+        }
+
+        /// Unpause the marketplace by the admin.
+        #[pallet::weight(1_000)]
+        pub fn unpause_marketplace(origin: OriginFor<T>) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            IsMarketplacePaused::<T>::kill();
+
+            Self::deposit_event(Event::MarketplaceUnpaused);
+
+            Ok(())
+        }
+
+        /// Pause or unpause a kind of calls by the admin, e.g. when a bug is found in it.
+        #[pallet::weight(1_000)]
+        pub fn set_call_paused(origin: OriginFor<T>, call: CallKind, paused: bool) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            if paused {
+                PausedCalls::<T>::insert(call, ());
+            } else {
+                PausedCalls::<T>::remove(call);
+            }
+
+            Self::deposit_event(Event::CallPaused(call, paused));
+
+            Ok(())
+        }
+
+        /// Set the mint phase by the admin.
+        #[pallet::weight(1_000)]
+        pub fn set_mint_phase(origin: OriginFor<T>, phase: MintPhase) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            CurrentMintPhase::<T>::put(phase);
+
+            Self::deposit_event(Event::MintPhaseSet(phase));
+
+            Ok(())
+        }
+
+        /// Set the mint quota of an account in the allowlist phase by the admin.
+        /// Zero removes the account from the allowlist.
+        #[pallet::weight(1_000)]
+        pub fn set_mint_allowlist(origin: OriginFor<T>, who: T::AccountId, quota: u32) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            if quota == 0 {
+                MintAllowlist::<T>::remove(&who);
+            } else {
+                MintAllowlist::<T>::insert(&who, quota);
+            }
+
+            Self::deposit_event(Event::MintAllowlistSet(who, quota));
+
+            Ok(())
+        }
+
+        /// Set the cap and the price of the public phase by the admin.
+        /// The cap counts all kitties minted in the public phase, including the ones already minted.
+        #[pallet::weight(1_000)]
+        pub fn set_public_mint(origin: OriginFor<T>, cap: Option<u32>, price: BalanceOf<T>) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            PublicMintCap::<T>::set(cap);
+            MintPrice::<T>::put(price);
+
+            Self::deposit_event(Event::PublicMintSet(cap, price));
+
+            Ok(())
+        }
+
+        /// Set the stake for each new kitty or new owner by the admin.
+        /// None falls back to the constant `StakeForEachKitty`.
+        /// The kitties keep the stake reserved from their owners until they change hands.
+        #[pallet::weight(1_000)]
+        pub fn set_stake_amount(origin: OriginFor<T>, stake: Option<BalanceOf<T>>) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            if let Some(stake) = stake {
+                ensure!(
+                    !stake.is_zero() && stake >= T::Currency::minimum_balance(),
+                    Error::<T>::InvalidStakeAmount
+                );
+            }
+
+            StakeAmount::<T>::set(stake);
+
+            Self::deposit_event(Event::StakeAmountSet(Self::stake_amount()));
+
+            Ok(())
+        }
+
+        /// Claim the first kitty of a new account, free of fees and without stake, with a
+        /// voucher of the voucher issuer. It's an unsigned transaction, validated by
+        /// `validate_unsigned`, so the account needs no balance. Each account claims once.
+        #[pallet::weight((1_000, DispatchClass::Normal, Pays::No))]
+        pub fn claim_starter_kitty(
+            origin: OriginFor<T>,
+            voucher: StarterVoucherOf<T>,
+            signature: T::OffchainSignature,
+        ) -> DispatchResult {
+            ensure_none(origin)?;
+            Self::ensure_call_enabled(CallKind::Create)?;
+            Self::check_voucher(&voucher, &signature)?;
+            Self::ensure_starter_mint_open()?;
+
+            let dna = Self::random_value(&voucher.who);
+            let kitty_id = Self::new_kitty(&voucher.who, dna, true, Zero::zero())?;
+            StarterKittyClaimed::<T>::insert(&voucher.who, ());
+            Self::note_mint(&voucher.who);
+
+            Self::deposit_event(Event::KittyCreated { owner: voucher.who, kitty_id, dna });
+
+            Ok(())
+        }
+
+        /// Set the account signing the vouchers of the starter kitties by the admin.
+        /// None stops the claims.
+        #[pallet::weight(1_000)]
+        pub fn set_voucher_issuer(origin: OriginFor<T>, issuer: Option<T::AccountId>) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            VoucherIssuer::<T>::set(issuer.clone());
+
+            Self::deposit_event(Event::VoucherIssuerSet(issuer));
 
             Ok(())
         }
 
     }
 
+    /// The custom error of a starter kitty claim while the mint is closed or the supply is exhausted.
+    pub const STARTER_MINT_UNAVAILABLE: u8 = 2;
+
+    #[pallet::validate_unsigned]
+    impl<T: Config> ValidateUnsigned for Pallet<T> {
+        type Call = Call<T>;
+
+        /// Accept the claims of starter kitties with a valid voucher, once for each account.
+        fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+            let (voucher, signature) = match call {
+                Call::claim_starter_kitty(voucher, signature) => (voucher, signature),
+                _ => return InvalidTransaction::Call.into(),
+            };
+
+            Self::check_voucher(voucher, signature).map_err(|e| match e {
+                Error::<T>::VoucherExpired | Error::<T>::StarterKittyAlreadyClaimed => InvalidTransaction::Stale,
+                _ => InvalidTransaction::BadProof,
+            })?;
+            Self::ensure_starter_mint_open()
+                .map_err(|_| InvalidTransaction::Custom(STARTER_MINT_UNAVAILABLE))?;
+
+            let now = frame_system::Pallet::<T>::block_number();
+            ValidTransaction::with_tag_prefix("KittiesStarter")
+                .and_provides(&voucher.who)
+                .longevity(voucher.expiry.saturating_sub(now).saturated_into::<u64>().max(1))
+                .propagate(true)
+                .build()
+        }
+    }
+
     // Helper functions.
     impl<T: Config> Pallet<T> {
+        /// Check that the kitties, owners and listings are consistent:
+        /// every kitty has an owner and every owner a kitty, only the kitties are listed,
+        /// and the counters match the kitties.
+        #[cfg(any(feature = "try-runtime", feature = "fuzzing", test))]
+        pub fn do_try_state() -> Result<(), &'static str> {
+            let mut live = 0u32;
+            for kitty_id in Kitties::<T>::iter_keys() {
+                ensure!(Owner::<T>::contains_key(kitty_id), "A kitty has no owner");
+                ensure!(kitty_id < NextKittyId::<T>::get(), "A kitty id is not below NextKittyId");
+                live += 1;
+            }
+            ensure!(TotalLiveKitties::<T>::get() == live.into(), "TotalLiveKitties is not the count of the kitties");
+            ensure!(
+                Owner::<T>::iter_keys().all(|kitty_id| Kitties::<T>::contains_key(kitty_id)),
+                "An owner has no kitty"
+            );
+            ensure!(
+                ListForSale::<T>::iter_keys().all(|kitty_id| Kitties::<T>::contains_key(kitty_id)),
+                "A listing has no kitty"
+            );
+            Ok(())
+        }
+
+        /// The stake for each new kitty or new owner.
+        pub fn stake_amount() -> BalanceOf<T> {
+            StakeAmount::<T>::get().unwrap_or_else(T::StakeForEachKitty::get)
+        }
+
+        /// Whether a kind of calls is paused by the admin.
+        pub fn is_call_paused(call: CallKind) -> bool {
+            PausedCalls::<T>::contains_key(call)
+        }
+
+        fn ensure_call_enabled(call: CallKind) -> DispatchResult {
+            ensure!(!Self::is_call_paused(call), Error::<T>::CallIsPaused);
+            Ok(())
+        }
+
+        // Helper function for create().
+        // Check the mint phase, and use the allowlist quota or pay the mint price.
+        fn check_mint(who: &T::AccountId) -> DispatchResult {
+            match Self::mint_phase() {
+                MintPhase::Closed => Err(Error::<T>::MintClosed.into()),
+                MintPhase::Allowlist => MintAllowlist::<T>::try_mutate_exists(who, |quota| -> DispatchResult {
+                    let remaining = quota.filter(|q| *q > 0).ok_or(Error::<T>::NotInMintAllowlist)?;
+                    *quota = Some(remaining - 1).filter(|q| *q > 0);
+                    Ok(())
+                }),
+                MintPhase::Public => {
+                    let minted = Self::public_minted();
+                    if let Some(cap) = Self::public_mint_cap() {
+                        ensure!(minted < cap, Error::<T>::PublicMintCapReached);
+                    }
+                    let price = Self::mint_price();
+                    if !price.is_zero() {
+                        T::Currency::transfer(who, &T::Treasury::get(), price, frame_support::traits::ExistenceRequirement::KeepAlive)?;
+                    }
+                    PublicMinted::<T>::put(minted.saturating_add(1));
+                    Ok(())
+                },
+            }
+        }
+
+        /// The number of kitties which can still be minted or bred.
+        pub fn remaining_supply() -> u32 {
+            T::MaxSupply::get()
+                .saturating_sub(Self::gen0_minted())
+                .saturating_sub(Self::bred_minted())
+        }
+
+        /// The number of gen-0 kitties which can still be minted.
+        pub fn remaining_gen0_supply() -> u32 {
+            T::MaxGen0Supply::get()
+                .saturating_sub(Self::gen0_minted())
+                .min(Self::remaining_supply())
+        }
+
+        /// The number of kitties created or bred by an account in the current era.
+        pub fn mints_in_current_era(who: &T::AccountId) -> u32 {
+            let (era, count) = MintsInEra::<T>::get(who);
+            if era == Self::current_mint_era() { count } else { 0 }
+        }
+
+        pub(crate) fn current_mint_era() -> T::BlockNumber {
+            frame_system::Pallet::<T>::block_number() / T::MintEraLength::get().max(1u32.into())
+        }
+
+        // Count a kitty created or bred by `who` for the mint rate limit.
+        fn note_mint(who: &T::AccountId) {
+            let era = Self::current_mint_era();
+            MintsInEra::<T>::mutate(who, |(last_era, count)| {
+                if *last_era != era {
+                    *last_era = era;
+                    *count = 0;
+                }
+                *count = count.saturating_add(1);
+            });
+        }
+
+        /// The lowest price of the kitties listed for sale.
+        pub fn floor_price() -> Option<BalanceOf<T>> {
+            ListForSale::<T>::iter_values().min()
+        }
+
+        /// The latest sale of a kitty.
+        pub fn last_sale(kitty_id: T::KittyIndex) -> Option<SaleRecordOf<T>> {
+            Self::kitty_sales(kitty_id).last().cloned()
+        }
+
+        // Push a sale into a ring buffer of sales, dropping the oldest one when it's full.
+        fn push_sale<V, S: Get<u32>>(sales: &mut BoundedVec<V, S>, sale: V) {
+            if !sales.is_empty() && sales.len() >= S::get() as usize {
+                sales.remove(0);
+            }
+            let _ = sales.try_push(sale);
+        }
+
+        /// The stake reserved from the owner of a kitty.
+        pub fn kitty_stake(kitty_id: T::KittyIndex) -> BalanceOf<T> {
+            KittyStake::<T>::get(kitty_id).unwrap_or_else(T::StakeForEachKitty::get)
+        }
+
         fn random_value(sender: &T::AccountId) -> [u8; 16] {
             let payload = (
                 T::Randomness::random_seed(),
@@ -237,30 +1298,217 @@ pub mod pallet {
             payload.using_encoded(blake2_128)
         }
 
+        /// The message signed for `transfer_with_signature`: the tag, the genesis hash and the
+        /// payload, SCALE encoded, so the signature is valid on this chain only.
+        pub fn transfer_signing_message(payload: &TransferPayloadOf<T>) -> Vec<u8> {
+            (TRANSFER_SIGNING_TAG, frame_system::Pallet::<T>::block_hash(T::BlockNumber::zero()), payload).encode()
+        }
+
         // Helper function for optimizing the codes from create() and transfer().
-        fn new_kitty_with_stake(owner: &T::AccountId, dna: [u8; 16]) -> DispatchResult {
+        fn new_kitty_with_stake(owner: &T::AccountId, dna: [u8; 16], gen0: bool) -> Result<T::KittyIndex, DispatchError> {
+            Self::new_kitty(owner, dna, gen0, Self::stake_amount())
+        }
 
-            let kitty_id = match Self::kitties_count() {
-                Some(id) => {
-                    ensure!(id != T::KittyIndex::max_value(), Error::<T>::KittiesCountOverflow);
-                    id
-                },
-                None => 0u32.into()
-            };
+        // Mint a kitty, reserving `stake` from the owner.
+        // The gen-0 kitties count for both MaxGen0Supply and MaxSupply, the bred ones for MaxSupply.
+        fn new_kitty(owner: &T::AccountId, dna: [u8; 16], gen0: bool, stake: BalanceOf<T>) -> Result<T::KittyIndex, DispatchError> {
+            ensure!(Self::remaining_supply() > 0, Error::<T>::SupplyExhausted);
+            ensure!(!gen0 || Self::remaining_gen0_supply() > 0, Error::<T>::Gen0SupplyExhausted);
 
-            let stake = T::StakeForEachKitty::get();
+            let kitty_id = Self::next_kitty_id();
+            ensure!(kitty_id != T::KittyIndex::max_value(), Error::<T>::KittiesCountOverflow);
 
             T::Currency::reserve(&owner, stake)
                 .map_err(|_| Error::<T>::NotEnoughBalanceForStaking)?;
 
-            Kitties::<T>::insert(kitty_id, Some(Kitty(dna)));
-            Owner::<T>::insert(kitty_id, Some(owner.clone()));
-            KittiesCount::<T>::put(kitty_id + 1u32.into());
+            KittyStake::<T>::insert(kitty_id, stake);
+            Kitties::<T>::insert(kitty_id, Kitty(dna));
+            Owner::<T>::insert(kitty_id, owner.clone());
+            NextKittyId::<T>::put(kitty_id + 1u32.into());
+            TotalLiveKitties::<T>::mutate(|n| *n += 1u32.into());
+            if gen0 {
+                Gen0Minted::<T>::mutate(|n| *n += 1);
+                KittyLineage::<T>::insert(kitty_id, Lineage::default());
+            } else {
+                BredMinted::<T>::mutate(|n| *n += 1);
+            }
+
+            Ok(kitty_id)
+        }
 
-            Self::deposit_event(Event::KittyCreated(owner.clone(), kitty_id));
+        // The starter kitties are minted out of the closed phase, the vouchers standing for the
+        // allowlist, and within the gen-0 and total supply like the other gen-0 kitties.
+        fn ensure_starter_mint_open() -> DispatchResult {
+            ensure!(Self::mint_phase() != MintPhase::Closed, Error::<T>::MintClosed);
+            ensure!(Self::remaining_supply() > 0, Error::<T>::SupplyExhausted);
+            ensure!(Self::remaining_gen0_supply() > 0, Error::<T>::Gen0SupplyExhausted);
+            Ok(())
+        }
+
+        // A voucher for an account which hasn't claimed its starter kitty yet, signed by the issuer.
+        fn check_voucher(voucher: &StarterVoucherOf<T>, signature: &T::OffchainSignature) -> Result<(), Error<T>> {
+            let issuer = Self::voucher_issuer().ok_or(Error::<T>::NoVoucherIssuer)?;
+            ensure!(
+                voucher.using_encoded(|message| signature.verify(message, &issuer)),
+                Error::<T>::InvalidVoucher
+            );
+            ensure!(frame_system::Pallet::<T>::block_number() <= voucher.expiry, Error::<T>::VoucherExpired);
+            ensure!(!StarterKittyClaimed::<T>::contains_key(&voucher.who), Error::<T>::StarterKittyAlreadyClaimed);
+            Ok(())
+        }
+
+        // Clear the listing of a kitty, if it's listed.
+        fn delist(owner: &T::AccountId, kitty_id: T::KittyIndex) {
+            if ListForSale::<T>::take(kitty_id).is_some() {
+                Self::deposit_event(Event::KittyDelisted { owner: owner.clone(), kitty_id });
+            }
+        }
+
+        // Helper function for release() and force_burn().
+        // Remove a kitty with everything attached to it, and return the stake and deposits.
+        fn remove_kitty(owner: &T::AccountId, kitty_id: T::KittyIndex) {
+            T::Currency::unreserve(owner, Self::kitty_stake(kitty_id));
+            KittyStake::<T>::remove(kitty_id);
+            if let Some(name) = KittyNames::<T>::take(kitty_id) {
+                T::Currency::unreserve(&name.depositor, name.deposit);
+            }
+            if let Some(metadata) = KittyMetadata::<T>::take(kitty_id) {
+                T::Currency::unreserve(&metadata.depositor, metadata.deposit);
+            }
+
+            Self::delist(owner, kitty_id);
+            Kitties::<T>::remove(kitty_id);
+            Owner::<T>::remove(kitty_id);
+            KittyLineage::<T>::remove(kitty_id);
+            KittySales::<T>::remove(kitty_id);
+            LoanOffers::<T>::remove(kitty_id);
+            TotalLiveKitties::<T>::mutate(|n| *n = n.saturating_sub(1u32.into()));
+        }
 
+        // A kitty can't change hands while it is lent or locked by other pallets.
+        fn ensure_transferable(kitty_id: T::KittyIndex) -> DispatchResult {
+            ensure!(!KittyUser::<T>::contains_key(kitty_id), Error::<T>::KittyOnLoan);
+            ensure!(!Self::is_locked(kitty_id), Error::<T>::KittyLocked);
             Ok(())
         }
 
+        // Helper function for set_kitty_name() and set_kitty_metadata().
+        // Reserve the deposit for `data` from `who` and return the old deposit to its depositor.
+        // Return None if `data` is empty, which means the attachment is cleared.
+        fn attach<Data: AsRef<[u8]>>(
+            who: &T::AccountId,
+            old: Option<(T::AccountId, BalanceOf<T>)>,
+            data: Data,
+        ) -> Result<Option<KittyAttachment<T::AccountId, BalanceOf<T>, Data>>, DispatchError> {
+            let len = data.as_ref().len() as u32;
+            let deposit = T::DepositPerByte::get().saturating_mul(len.into());
+
+            // The same depositor reserves or gets back only the difference with the old deposit.
+            match old {
+                Some((depositor, old_deposit)) if depositor == *who => {
+                    if deposit > old_deposit {
+                        T::Currency::reserve(who, deposit - old_deposit)
+                            .map_err(|_| Error::<T>::NotEnoughBalanceForDeposit)?;
+                    } else {
+                        T::Currency::unreserve(who, old_deposit - deposit);
+                    }
+                },
+                old => {
+                    T::Currency::reserve(who, deposit)
+                        .map_err(|_| Error::<T>::NotEnoughBalanceForDeposit)?;
+                    if let Some((depositor, old_deposit)) = old {
+                        T::Currency::unreserve(&depositor, old_deposit);
+                    }
+                },
+            }
+
+            if len == 0 {
+                return Ok(None);
+            }
+            Ok(Some(KittyAttachment { depositor: who.clone(), deposit, data }))
+        }
+
    }
+
+    impl<T: Config> KittyLock<T::KittyIndex> for Pallet<T> {
+        fn lock(kitty_id: T::KittyIndex, reason: LockIdentifier) -> DispatchResult {
+            ensure!(Kitties::<T>::contains_key(kitty_id), Error::<T>::InvalidKittyIndex);
+            ensure!(!Locks::<T>::contains_key(kitty_id, reason), Error::<T>::AlreadyLocked);
+            ensure!(!ExclusiveLocks::<T>::contains_key(kitty_id), Error::<T>::KittyLocked);
+
+            Locks::<T>::insert(kitty_id, reason, ());
+
+            Self::deposit_event(Event::KittyLocked(kitty_id, reason));
+
+            Ok(())
+        }
+
+        fn lock_exclusive(kitty_id: T::KittyIndex, reason: LockIdentifier) -> DispatchResult {
+            ensure!(Kitties::<T>::contains_key(kitty_id), Error::<T>::InvalidKittyIndex);
+            Self::ensure_transferable(kitty_id)?;
+
+            Locks::<T>::insert(kitty_id, reason, ());
+            ExclusiveLocks::<T>::insert(kitty_id, reason);
+
+            Self::deposit_event(Event::KittyLocked(kitty_id, reason));
+
+            Ok(())
+        }
+
+        fn unlock(kitty_id: T::KittyIndex, reason: LockIdentifier) -> DispatchResult {
+            ensure!(Locks::<T>::contains_key(kitty_id, reason), Error::<T>::NotLocked);
+
+            Locks::<T>::remove(kitty_id, reason);
+            if ExclusiveLocks::<T>::get(kitty_id) == Some(reason) {
+                ExclusiveLocks::<T>::remove(kitty_id);
+            }
+
+            Self::deposit_event(Event::KittyUnlocked(kitty_id, reason));
+
+            Ok(())
+        }
+
+        fn is_locked(kitty_id: T::KittyIndex) -> bool {
+            Locks::<T>::iter_prefix(kitty_id).next().is_some()
+        }
+    }
+
+    impl<T: Config> KittyInspect<T::AccountId, T::KittyIndex> for Pallet<T> {
+        fn owner_of(kitty_id: T::KittyIndex) -> Option<T::AccountId> {
+            Owner::<T>::get(kitty_id)
+        }
+
+        fn dna_of(kitty_id: T::KittyIndex) -> Option<[u8; 16]> {
+            Kitties::<T>::get(kitty_id).map(|kitty| kitty.0)
+        }
+
+        fn is_lent(kitty_id: T::KittyIndex) -> bool {
+            KittyUser::<T>::contains_key(kitty_id)
+        }
+    }
+
+    impl<T: Config> KittyTransfer<T::AccountId, T::KittyIndex> for Pallet<T> {
+        fn transfer_kitty(from: &T::AccountId, to: &T::AccountId, kitty_id: T::KittyIndex) -> DispatchResult {
+            // Ensure transfer only from the OWNER of kitties.
+            ensure!(Some(from.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotOwner);
+            Self::ensure_transferable(kitty_id)?;
+
+            let stake_amount = Self::stake_amount();
+
+            // Staking from new owner and unstaking from the ex-ownder
+            T::Currency::reserve(to, stake_amount)
+                .map_err(|_| Error::<T>::NotEnoughBalanceForStaking)?;
+            T::Currency::unreserve(from, Self::kitty_stake(kitty_id));
+
+            // Update storage. The listing and the loan offer of the ex-owner are removed.
+            KittyStake::<T>::insert(kitty_id, stake_amount);
+            Owner::<T>::insert(kitty_id, to.clone());
+            Self::delist(from, kitty_id);
+            LoanOffers::<T>::remove(kitty_id);
+            // Emit the event.
+            Self::deposit_event(Event::KittyTransferred(from.clone(), to.clone(), kitty_id));
+
+            Ok(())
+        }
+    }
 }
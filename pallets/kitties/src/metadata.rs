@@ -0,0 +1,146 @@
+//! Kitty metadata in the style of ERC-721, cached by the offchain worker.
+//!
+//! The offchain worker writes the metadata JSON of each new kitty into the persistent offchain
+//! local storage under `metadata_key`, for the node to serve it without an external server.
+//! The metadata is drawn from the DNA and the lineage, so it's fixed when the kitty is created,
+//! but a reorg may create another kitty with the same id. So each entry is a `CachedMetadata`
+//! with the DNA it was drawn from, and the readers check the DNA against the chain.
+
+use codec::{Decode, Encode};
+use core::fmt::{self, Display, Write};
+use sp_runtime::{offchain::StorageKind, traits::{UniqueSaturatedInto, Zero}, RuntimeDebug};
+use sp_std::prelude::*;
+use crate::{genes::Genes, pallet::*};
+
+/// The prefix of the metadata keys in the persistent offchain local storage.
+pub const METADATA_PREFIX: &[u8] = b"kitties::metadata::";
+
+/// The key of the next kitty whose metadata isn't cached yet.
+const CURSOR_KEY: &[u8] = b"kitties::metadata-cursor";
+
+/// The most kitties cached by one run of the offchain worker, the rest wait for the next runs.
+const MAX_METADATA_PER_RUN: u32 = 100;
+
+/// The key of the metadata of a kitty in the persistent offchain local storage.
+pub fn metadata_key<KittyIndex: Encode>(kitty_id: &KittyIndex) -> Vec<u8> {
+	[METADATA_PREFIX, &kitty_id.encode()[..]].concat()
+}
+
+/// An entry of the metadata cache: the metadata JSON and the DNA it was drawn from.
+#[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
+pub struct CachedMetadata {
+	pub dna: [u8; 16],
+	pub json: Vec<u8>,
+}
+
+/// The cached metadata of a kitty, whatever the DNA.
+pub fn cached_metadata<KittyIndex: Encode>(kitty_id: &KittyIndex) -> Option<CachedMetadata> {
+	sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &metadata_key(kitty_id))
+		.and_then(|entry| CachedMetadata::decode(&mut &entry[..]).ok())
+}
+
+/// Cache the metadata of the kitties created since the last run.
+/// The workers of concurrent blocks may cache the same kitties twice, which is harmless.
+pub fn cache_new_metadata<T: Config>() {
+	let next = NextKittyId::<T>::get();
+	let cursor = sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, CURSOR_KEY)
+		.and_then(|cursor| T::KittyIndex::decode(&mut &cursor[..]).ok())
+		.unwrap_or_else(Zero::zero);
+
+	// After a reorg, the last kitties before the cursor may have another DNA on the best chain:
+	// go back over them, and cache them again with the new ones.
+	let mut kitty_id = cursor.min(next);
+	let mut count = 0;
+	while !kitty_id.is_zero() && count < MAX_METADATA_PER_RUN {
+		let previous = kitty_id - 1u32.into();
+		match Kitties::<T>::get(previous) {
+			Some(kitty) if cached_metadata(&previous).map(|cached| cached.dna) == Some(kitty.0) => break,
+			_ => kitty_id = previous,
+		}
+		count += 1;
+	}
+
+	let mut count = 0;
+	while kitty_id < next && count < MAX_METADATA_PER_RUN {
+		if let Some(kitty) = Kitties::<T>::get(kitty_id) {
+			let json = metadata_json(kitty_id, &kitty.0, KittyLineage::<T>::get(kitty_id).as_ref());
+			let entry = CachedMetadata { dna: kitty.0, json };
+			sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, &metadata_key(&kitty_id), &entry.encode());
+		}
+		kitty_id = kitty_id + 1u32.into();
+		count += 1;
+	}
+
+	sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, CURSOR_KEY, &kitty_id.encode());
+}
+
+/// The metadata JSON of a kitty, with its traits as the attributes.
+/// The generation and the parents are null if the lineage is unknown.
+pub fn metadata_json<KittyIndex>(kitty_id: KittyIndex, dna: &[u8; 16], lineage: Option<&Lineage<KittyIndex>>) -> Vec<u8>
+where
+	KittyIndex: Copy + UniqueSaturatedInto<u64>,
+{
+	let id = |kitty_id: KittyIndex| -> u64 { kitty_id.unique_saturated_into() };
+	let genes = Genes::from_dna(dna);
+	let mut json = Json(Vec::new());
+
+	// Writing to a Vec can't fail.
+	let _ = match lineage {
+		Some(lineage) => write!(
+			json,
+			r#"{{"name":"Kitty #{}","description":"A generation {} kitty.","dna":"{}","generation":{},"parents":"#,
+			id(kitty_id), lineage.generation, Hex("0x", dna), lineage.generation,
+		),
+		None => write!(
+			json,
+			r#"{{"name":"Kitty #{}","description":"A kitty of unknown lineage.","dna":"{}","generation":null,"parents":"#,
+			id(kitty_id), Hex("0x", dna),
+		),
+	};
+	let _ = match lineage.and_then(|lineage| lineage.parents) {
+		Some((parent1, parent2)) => write!(json, "[{},{}]", id(parent1), id(parent2)),
+		None => write!(json, "null"),
+	};
+	let _ = write!(
+		json,
+		concat!(
+			r#","attributes":["#,
+			r#"{{"trait_type":"Body Color","value":"{}"}},"#,
+			r#"{{"trait_type":"Pattern Color","value":"{}"}},"#,
+			r#"{{"trait_type":"Eye Color","value":"{}"}},"#,
+			r#"{{"trait_type":"Pattern","value":"{:?}"}},"#,
+			r#"{{"trait_type":"Eyes","value":"{:?}"}},"#,
+			r#"{{"trait_type":"Ears","value":"{:?}"}},"#,
+			r#"{{"trait_type":"Mouth","value":"{:?}"}},"#,
+			r#"{{"trait_type":"Background Hue","value":{}}}"#,
+		),
+		Hex("#", &genes.body_color), Hex("#", &genes.pattern_color), Hex("#", &genes.eye_color),
+		genes.pattern, genes.eye_shape, genes.ear_shape, genes.mouth_shape,
+		genes.background_hue,
+	);
+	if let Some(lineage) = lineage {
+		let _ = write!(json, r#",{{"display_type":"number","trait_type":"Generation","value":{}}}"#, lineage.generation);
+	}
+	let _ = write!(json, "]}}");
+
+	json.0
+}
+
+struct Json(Vec<u8>);
+
+impl Write for Json {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		self.0.extend_from_slice(s.as_bytes());
+		Ok(())
+	}
+}
+
+// Bytes in hex after a prefix, e.g. 0x for the DNA or # for a color.
+struct Hex<'a>(&'static str, &'a [u8]);
+
+impl Display for Hex<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(self.0)?;
+		self.1.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+	}
+}
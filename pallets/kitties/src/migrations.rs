@@ -0,0 +1,55 @@
+//! Storage migrations for the kitties pallet.
+
+use crate::pallet::*;
+use frame_support::{
+	storage::migration::take_storage_value,
+	traits::{Get, PalletInfoAccess},
+	weights::Weight,
+};
+use sp_runtime::traits::SaturatedConversion;
+
+/// Split `KittiesCount` into `NextKittyId` and `TotalLiveKitties`.
+/// `KittiesCount` is the next id, and the live kitties are counted, as some may be released.
+/// The minted kitties count against the supply caps: those not counted as bred count as gen-0,
+/// as the kitties minted before the caps weren't told apart.
+pub fn migrate_to_v2<T: Config>() -> Weight {
+	if StorageVersion::<T>::get() != Releases::V1 {
+		return T::DbWeight::get().reads(1);
+	}
+
+	if let Some(count) = take_storage_value::<T::KittyIndex>(Pallet::<T>::name().as_bytes(), b"KittiesCount", &[]) {
+		NextKittyId::<T>::put(count);
+	}
+	let live = Kitties::<T>::iter_keys().count() as u32;
+	TotalLiveKitties::<T>::put(T::KittyIndex::from(live));
+	let minted: u32 = NextKittyId::<T>::get().saturated_into();
+	Gen0Minted::<T>::put(minted.saturating_sub(BredMinted::<T>::get()));
+	StorageVersion::<T>::put(Releases::V2);
+
+	T::DbWeight::get().reads_writes(4 + live as Weight, 5)
+}
+
+/// Store the values of `Kitties`, `Owner` and `ListForSale` without Option.
+/// The entries of None are removed, e.g. the kitties listed as not for sale.
+pub fn migrate_to_v3<T: Config>() -> Weight {
+	if StorageVersion::<T>::get() != Releases::V2 {
+		return T::DbWeight::get().reads(1);
+	}
+
+	let mut translated: Weight = 0;
+	Kitties::<T>::translate::<Option<Kitty>, _>(|_, kitty| {
+		translated += 1;
+		kitty
+	});
+	Owner::<T>::translate::<Option<T::AccountId>, _>(|_, owner| {
+		translated += 1;
+		owner
+	});
+	ListForSale::<T>::translate::<Option<BalanceOf<T>>, _>(|_, price| {
+		translated += 1;
+		price
+	});
+	StorageVersion::<T>::put(Releases::V3);
+
+	T::DbWeight::get().reads_writes(1 + translated, 1 + translated)
+}
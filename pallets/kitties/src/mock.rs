@@ -1,8 +1,8 @@
 use crate as pallet_kitties;
 use sp_core::H256;
-use frame_support::parameter_types;
+use frame_support::{parameter_types, traits::{OnFinalize, OnInitialize}};
 use sp_runtime::{
-	traits::{BlakeTwo256, IdentityLookup}, testing::Header,
+	traits::{BlakeTwo256, IdentityLookup}, testing::{Header, TestSignature, UintAuthorityId},
 };
 use frame_system as system;
 
@@ -77,6 +77,21 @@ impl pallet_randomness_collective_flip::Config for Test {}
 
 parameter_types! {
     pub const StakeForEachKitty: u128 = 10_000;
+    pub const DepositPerByte: u128 = 100;
+    pub const MaxNameLength: u32 = 16;
+    pub const MaxMetadataLength: u32 = 64;
+    pub const MaxLoansPerBlock: u32 = 2;
+    pub const Treasury: u64 = 100;
+    pub const MaxSalesPerKitty: u32 = 2;
+    pub const MaxRecentSales: u32 = 3;
+    pub const MaxMintsPerEra: u32 = 3;
+    pub const MintEraLength: u64 = 10;
+}
+
+parameter_types! {
+    // In the storage, for the fuzzing harness to raise them.
+    pub storage MaxGen0Supply: u32 = 6;
+    pub storage MaxSupply: u32 = 8;
 }
 
 impl pallet_kitties::Config for Test {
@@ -85,11 +100,25 @@ impl pallet_kitties::Config for Test {
 	type KittyIndex = u32;
 	type StakeForEachKitty = StakeForEachKitty;
 	type Currency = Balances;
+	type DepositPerByte = DepositPerByte;
+	type MaxNameLength = MaxNameLength;
+	type MaxMetadataLength = MaxMetadataLength;
+	type MaxLoansPerBlock = MaxLoansPerBlock;
+	type AdminOrigin = frame_system::EnsureRoot<u64>;
+	type Treasury = Treasury;
+	type MaxGen0Supply = MaxGen0Supply;
+	type MaxSupply = MaxSupply;
+	type MaxSalesPerKitty = MaxSalesPerKitty;
+	type MaxRecentSales = MaxRecentSales;
+	type OffchainSignature = TestSignature;
+	type SigningPublicKey = UintAuthorityId;
+	type MaxMintsPerEra = MaxMintsPerEra;
+	type MintEraLength = MintEraLength;
 }
 
 // Helper macro.
 // Wrap System::assert_has_event() to macro assert_has_event!
-// Usage example: assert_has_event!(Event::<Test>::KittyCreated(1,0))
+// Usage example: assert_has_event!(Event::<Test>::KittyTransferred(1, 2, 0))
 #[macro_export]
 macro_rules! assert_has_event {
 	($x:expr) => {
@@ -112,3 +141,12 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 	ext.execute_with(|| System::set_block_number(1));
 	ext
 }
+
+// Run the blocks until block number `n`, with the hooks of the kitties pallet.
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		KittiesModule::on_finalize(System::block_number());
+		System::set_block_number(System::block_number() + 1);
+		KittiesModule::on_initialize(System::block_number());
+	}
+}
@@ -0,0 +1,82 @@
+//! The rate limit of minting kitties, checked before the transactions enter the pool.
+
+use codec::{Decode, Encode};
+use frame_support::traits::{Get, IsSubType};
+use sp_runtime::{
+	traits::{DispatchInfoOf, One, SaturatedConversion, Saturating, SignedExtension},
+	transaction_validity::{InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction},
+};
+use sp_std::{fmt, marker::PhantomData};
+use crate::pallet::*;
+
+/// The custom error of a transaction rejected by `CheckKittyRateLimit`.
+pub const MINT_RATE_LIMITED: u8 = 1;
+
+/// Reject the `create` and `breed` calls of the accounts which minted `MaxMintsPerEra` kitties
+/// in the current era, so a single account can't flood the transaction pool with mints.
+/// It's on top of the stake of each kitty.
+///
+/// A mint provides the tag of its account, era and slot, the number of kitties the account
+/// minted in the era. The mints validated against the same state take the same slot, so the
+/// pool keeps one pending mint of each account, and the next one waits for it to be included.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct CheckKittyRateLimit<T: Config + Send + Sync>(PhantomData<T>);
+
+impl<T: Config + Send + Sync> CheckKittyRateLimit<T> {
+	pub fn new() -> Self {
+		CheckKittyRateLimit(PhantomData)
+	}
+}
+
+impl<T: Config + Send + Sync> Default for CheckKittyRateLimit<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Config + Send + Sync> fmt::Debug for CheckKittyRateLimit<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "CheckKittyRateLimit")
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for CheckKittyRateLimit<T>
+where
+	<T as frame_system::Config>::Call: IsSubType<Call<T>>,
+{
+	const IDENTIFIER: &'static str = "CheckKittyRateLimit";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		let is_mint = matches!(call.is_sub_type(), Some(Call::create()) | Some(Call::breed(..)));
+		if !is_mint {
+			return Ok(ValidTransaction::default());
+		}
+		let slot = Pallet::<T>::mints_in_current_era(who);
+		if slot >= T::MaxMintsPerEra::get() {
+			return InvalidTransaction::Custom(MINT_RATE_LIMITED).into();
+		}
+
+		// Revalidate the pending mints in the next era, when the count restarts.
+		let era = Pallet::<T>::current_mint_era();
+		let era_end = (era + One::one()) * T::MintEraLength::get().max(One::one());
+		let now = frame_system::Pallet::<T>::block_number();
+		ValidTransaction::with_tag_prefix("KittyMint")
+			.and_provides((who, era, slot))
+			.longevity(era_end.saturating_sub(now).saturated_into::<u64>().max(1))
+			.build()
+	}
+}
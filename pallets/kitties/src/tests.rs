@@ -1,5 +1,7 @@
-use crate::mock::{Event as TestEvent, new_test_ext, KittiesModule, Origin, System, Test};
+use crate::mock::{Event as TestEvent, new_test_ext, run_to_block, Balances, KittiesModule, Origin, System, Test};
 use frame_support::{assert_ok, assert_noop};
+use sp_runtime::traits::BadOrigin;
+use crate::traits::KittyLock;
 use super::*;
 
 #[test]
@@ -7,18 +9,20 @@ fn create_works() {
 	new_test_ext().execute_with(|| {
 		let account_id: u64 = 1;
 		assert_ok!(KittiesModule::create(Origin::signed(account_id)));
-		assert_eq!(KittiesCount::<Test>::get(), Some(1));
+		assert_eq!(NextKittyId::<Test>::get(), 1);
+		assert_eq!(TotalLiveKitties::<Test>::get(), 1);
 		assert_eq!(Owner::<Test>::get(0), Some(1));
 		// Test the Event emitted already.
-		// Event::<Test>::KittyCreated(Owner, KittyIndex)
-		assert_has_event!(Event::<Test>::KittyCreated(1,0));
+		// Event::<Test>::KittyCreated { owner, kitty_id, dna }
+		let dna = Kitties::<Test>::get(0).unwrap().0;
+		assert_has_event!(Event::<Test>::KittyCreated { owner: 1, kitty_id: 0, dna });
 	});
 }
 
 #[test]
 fn create_failed_when_kittiescount_overflow() {
 	new_test_ext().execute_with(|| {
-		KittiesCount::<Test>::put(u32::max_value());
+		NextKittyId::<Test>::put(u32::max_value());
 		let account_id: u64 = 1;
 		assert_noop!(KittiesModule::create(Origin::signed(account_id)), Error::<Test>::KittiesCountOverflow);
 	});
@@ -70,19 +74,101 @@ fn transfer_failed_when_new_owner_not_enough_balance() {
 	});
 }
 
+#[test]
+fn transfer_with_signature_works() {
+	use sp_runtime::testing::TestSignature;
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		// AccountID =1 signs the transfer of index=0 to AccountID =2, relayed by AccountID =3.
+		let payload = TransferPayload { from: 1, to: 2, kitty_id: 0, nonce: 0, expiry: 10 };
+		let signature = TestSignature(1, KittiesModule::transfer_signing_message(&payload));
+		assert_ok!(KittiesModule::transfer_with_signature(Origin::signed(3), payload.clone(), signature.clone()));
+		assert_eq!(Owner::<Test>::get(0), Some(2));
+		assert_eq!(KittiesModule::transfer_nonce(1), 1);
+		assert_has_event!(Event::<Test>::KittyTransferred(1, 2, 0));
+
+		// The payload can't be replayed, e.g. after the kitty returns to AccountID =1.
+		assert_ok!(KittiesModule::transfer(Origin::signed(2), 1, 0));
+		assert_noop!(
+			KittiesModule::transfer_with_signature(Origin::signed(3), payload, signature),
+			Error::<Test>::InvalidTransferNonce
+		);
+	});
+}
+
+#[test]
+fn transfer_with_signature_failed_when_invalid() {
+	use sp_runtime::testing::TestSignature;
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		let payload = TransferPayload { from: 1, to: 2, kitty_id: 0, nonce: 0, expiry: 10 };
+		let sign = |signer: u64, payload: &TransferPayloadOf<Test>| {
+			TestSignature(signer, KittiesModule::transfer_signing_message(payload))
+		};
+
+		// Signed by AccountID =2, not the owner in the payload.
+		assert_noop!(
+			KittiesModule::transfer_with_signature(Origin::signed(3), payload.clone(), sign(2, &payload)),
+			Error::<Test>::InvalidSignature
+		);
+		// Signed for another recipient.
+		let other = TransferPayload { to: 3, ..payload.clone() };
+		assert_noop!(
+			KittiesModule::transfer_with_signature(Origin::signed(3), payload.clone(), sign(1, &other)),
+			Error::<Test>::InvalidSignature
+		);
+		// Signed by AccountID =2 for a kitty of AccountID =1.
+		let not_owner = TransferPayload { from: 2, ..payload.clone() };
+		assert_noop!(
+			KittiesModule::transfer_with_signature(Origin::signed(3), not_owner.clone(), sign(2, &not_owner)),
+			Error::<Test>::NotOwner
+		);
+		// Expired.
+		run_to_block(11);
+		assert_noop!(
+			KittiesModule::transfer_with_signature(Origin::signed(3), payload.clone(), sign(1, &payload)),
+			Error::<Test>::TransferPayloadExpired
+		);
+	});
+}
+
+#[test]
+fn transfer_with_signature_failed_when_signed_for_another_chain() {
+	use codec::Encode;
+	use sp_core::H256;
+	use sp_runtime::testing::TestSignature;
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		let payload = TransferPayload { from: 1, to: 2, kitty_id: 0, nonce: 0, expiry: 10 };
+
+		// Signed for a chain of another genesis.
+		let message = (TRANSFER_SIGNING_TAG, H256::repeat_byte(1), &payload).encode();
+		assert_noop!(
+			KittiesModule::transfer_with_signature(Origin::signed(3), payload.clone(), TestSignature(1, message)),
+			Error::<Test>::InvalidSignature
+		);
+		// The bare payload, without the tag and the genesis hash.
+		assert_noop!(
+			KittiesModule::transfer_with_signature(Origin::signed(3), payload.clone(), TestSignature(1, payload.encode())),
+			Error::<Test>::InvalidSignature
+		);
+	});
+}
+
 #[test]
 fn breed_works() {
 	new_test_ext().execute_with(|| {
-		// Prepare kitty index=0, by AccountID =1.
+		// Prepare kitty index=0 and 1, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
 		assert_ok!(KittiesModule::create(Origin::signed(1)));
-		// Prepare kitty index=1, by AccountID =2.
-		assert_ok!(KittiesModule::create(Origin::signed(2)));
 		// Breed a kitty index=2 from 0&1, by AccountID =1.
 		assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
-		assert_eq!(KittiesCount::<Test>::get(), Some(3));
+		assert_eq!(NextKittyId::<Test>::get(), 3);
+		assert_eq!(TotalLiveKitties::<Test>::get(), 3);
 		// Test the Event emitted already.
-		// Event::<Test>::KittyCreated(Owner, KittyIndex)
-		assert_has_event!(Event::<Test>::KittyCreated(1, 2));
+		// Event::<Test>::KittyBred { owner, child, parent1, parent2, dna }
+		let dna = Kitties::<Test>::get(2).unwrap().0;
+		assert_has_event!(Event::<Test>::KittyBred { owner: 1, child: 2, parent1: 0, parent2: 1, dna });
 	});
 }
 
@@ -111,12 +197,24 @@ fn breed_failed_when_same_parent() {
 }
 
 #[test]
-fn breed_failed_when_not_enough_balance_for_staking() {
+fn breed_failed_when_not_owner() {
 	new_test_ext().execute_with(|| {
-		// Prepare kitty index=0, by AccountID =1.
+		// Prepare kitty index=0, by AccountID =1, and index=1, by AccountID =2.
 		assert_ok!(KittiesModule::create(Origin::signed(1)));
-		// Prepare kitty index=1, by AccountID =2.
 		assert_ok!(KittiesModule::create(Origin::signed(2)));
+		assert_noop!(KittiesModule::breed(Origin::signed(1), 0, 1), Error::<Test>::NotOwner);
+		assert_noop!(KittiesModule::breed(Origin::signed(3), 0, 1), Error::<Test>::NotOwner);
+	});
+}
+
+#[test]
+fn breed_failed_when_not_enough_balance_for_staking() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0 and 1, by AccountID =1, moved to AccountID =3 without stake.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::force_transfer(Origin::root(), 3, 0));
+		assert_ok!(KittiesModule::force_transfer(Origin::root(), 3, 1));
 		// Account 3 has not enough balance for staing
 		assert_noop!(KittiesModule::breed(Origin::signed(3), 0, 1), Error::<Test>::NotEnoughBalanceForStaking);
 	});
@@ -132,8 +230,12 @@ fn sell_works() {
 		assert_ok!(KittiesModule::sell(Origin::signed(1), 0, Some(price)));
 		assert_eq!(ListForSale::<Test>::get(0), Some(price));
 		// Test the Event emitted already.
-		// KittyListed(T::AccountId, T::KittyIndex, Option<BalanceOf<T>>)
-		assert_has_event!(Event::<Test>::KittyListed(1, 0, Some(price)));
+		// KittyListed(T::AccountId, T::KittyIndex, BalanceOf<T>)
+		assert_has_event!(Event::<Test>::KittyListed(1, 0, price));
+		// Delist Kitty index=0.
+		assert_ok!(KittiesModule::sell(Origin::signed(1), 0, None));
+		assert!(!ListForSale::<Test>::contains_key(0));
+		assert_has_event!(Event::<Test>::KittyDelisted { owner: 1, kitty_id: 0 });
 	});
 }
 
@@ -160,8 +262,9 @@ fn buy_works() {
 		assert_ok!(KittiesModule::buy(Origin::signed(2), 0));
 		assert_eq!(Owner::<Test>::get(0), Some(2));
 		// Test the Event emitted.
-		// KittyTransferred(Seller, Buyer, KittyIndex)
-		assert_has_event!(Event::<Test>::KittyTransferred(1, 2, 0));
+		// KittySold(Seller, Buyer, KittyIndex, Price)
+		assert_has_event!(Event::<Test>::KittySold(1, 2, 0, price));
+		assert_has_event!(Event::<Test>::KittyDelisted { owner: 1, kitty_id: 0 });
 	});
 }
 
@@ -178,6 +281,14 @@ fn buy_failed_when_buyer_is_owner() {
 	});
 }
 
+#[test]
+fn buy_failed_when_invalid_kitty() {
+	new_test_ext().execute_with(|| {
+		// No KittyID =0.
+		assert_noop!(KittiesModule::buy(Origin::signed(2), 0), Error::<Test>::InvalidKittyIndex);
+	});
+}
+
 #[test]
 fn buy_failed_when_not_for_sale() {
 	new_test_ext().execute_with(|| {
@@ -201,3 +312,1121 @@ fn buy_failed_when_not_enough_balance() {
 		assert_noop!(KittiesModule::buy(Origin::signed(3), 0), Error::<Test>::NotEnoughBalanceForBuying);
 	});
 }
+
+#[test]
+fn set_kitty_name_works() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		// Name Kitty index=0, the deposit is 100 for each byte.
+		assert_ok!(KittiesModule::set_kitty_name(Origin::signed(1), 0, b"Tom".to_vec()));
+		assert_eq!(KittyNames::<Test>::get(0).unwrap().data.to_vec(), b"Tom".to_vec());
+		assert_eq!(Balances::reserved_balance(1), 10_000 + 300);
+		// Rename it, the deposit is updated.
+		assert_ok!(KittiesModule::set_kitty_name(Origin::signed(1), 0, b"Tommy".to_vec()));
+		assert_eq!(Balances::reserved_balance(1), 10_000 + 500);
+		// Clear the name with an empty name, the deposit is returned.
+		assert_ok!(KittiesModule::set_kitty_name(Origin::signed(1), 0, vec![]));
+		assert_eq!(KittyNames::<Test>::get(0), None);
+		assert_eq!(Balances::reserved_balance(1), 10_000);
+		// Test the Event emitted already.
+		// KittyNameSet(Who, KittyIndex)
+		assert_has_event!(Event::<Test>::KittyNameSet(1, 0));
+	});
+}
+
+#[test]
+fn set_kitty_name_failed_when_not_owner() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		// AccountID=2 is not the owner.
+		assert_noop!(KittiesModule::set_kitty_name(Origin::signed(2), 0, b"Tom".to_vec()), Error::<Test>::NotOwner);
+	});
+}
+
+#[test]
+fn set_kitty_name_failed_when_too_long() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		// MaxNameLength is 16 in the mock.
+		assert_noop!(KittiesModule::set_kitty_name(Origin::signed(1), 0, vec![b'a'; 17]), Error::<Test>::NameTooLong);
+	});
+}
+
+#[test]
+fn set_kitty_metadata_returns_deposit_to_depositor() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::set_kitty_metadata(Origin::signed(1), 0, b"ipfs://kitty".to_vec()));
+		assert_eq!(Balances::reserved_balance(1), 10_000 + 1_200);
+		// Transfer to AccountID=2, who replaces the metadata.
+		assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+		assert_ok!(KittiesModule::set_kitty_metadata(Origin::signed(2), 0, b"ipfs://cat".to_vec()));
+		// AccountID=1 gets the deposit back, AccountID=2 reserves the new one.
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(2), 10_000 + 1_000);
+		assert_has_event!(Event::<Test>::KittyMetadataSet(2, 0));
+	});
+}
+
+#[test]
+fn set_kitty_metadata_failed_when_too_long() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		// MaxMetadataLength is 64 in the mock.
+		assert_noop!(KittiesModule::set_kitty_metadata(Origin::signed(1), 0, vec![b'a'; 65]), Error::<Test>::MetadataTooLong);
+	});
+}
+
+#[test]
+fn force_clear_metadata_works() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, with a name and metadata.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::set_kitty_name(Origin::signed(1), 0, b"Tom".to_vec()));
+		assert_ok!(KittiesModule::set_kitty_metadata(Origin::signed(1), 0, b"ipfs://kitty".to_vec()));
+		// Only root can clear them.
+		assert_noop!(KittiesModule::force_clear_metadata(Origin::signed(1), 0), BadOrigin);
+		assert_ok!(KittiesModule::force_clear_metadata(Origin::root(), 0));
+		assert_eq!(KittyNames::<Test>::get(0), None);
+		assert_eq!(KittyMetadata::<Test>::get(0), None);
+		// The deposits are slashed, only the stake is still reserved.
+		assert_eq!(Balances::reserved_balance(1), 10_000);
+		assert_eq!(Balances::free_balance(1), 10_000_000_000 - 10_000 - 1_500);
+		assert_has_event!(Event::<Test>::KittyMetadataCleared(0));
+	});
+}
+
+#[test]
+fn force_clear_metadata_failed_when_kitty_not_exist() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(KittiesModule::force_clear_metadata(Origin::root(), 0), Error::<Test>::InvalidKittyIndex);
+	});
+}
+
+#[test]
+fn set_kitty_name_reserves_only_the_difference() {
+	use frame_support::traits::Currency;
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, named with a deposit of 300.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::set_kitty_name(Origin::signed(1), 0, b"Tom".to_vec()));
+		Balances::make_free_balance_be(&1, 200);
+		// A name of the same length needs no more balance.
+		assert_ok!(KittiesModule::set_kitty_name(Origin::signed(1), 0, b"Bob".to_vec()));
+		assert_eq!(Balances::reserved_balance(1), 10_000 + 300);
+		// A longer name reserves the extra bytes only, a shorter one returns them.
+		assert_ok!(KittiesModule::set_kitty_name(Origin::signed(1), 0, b"Bobby".to_vec()));
+		assert_eq!(Balances::free_balance(1), 0);
+		assert_noop!(
+			KittiesModule::set_kitty_name(Origin::signed(1), 0, b"Bobby1".to_vec()),
+			Error::<Test>::NotEnoughBalanceForDeposit
+		);
+		assert_ok!(KittiesModule::set_kitty_name(Origin::signed(1), 0, b"Bo".to_vec()));
+		assert_eq!(Balances::reserved_balance(1), 10_000 + 200);
+		assert_eq!(Balances::free_balance(1), 300);
+	});
+}
+
+#[test]
+fn lend_works() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		// Lend Kitty index=0 to AccountID=2 for 10 blocks with a fee=1_000.
+		assert_ok!(KittiesModule::lend(Origin::signed(1), 0, 2, 10, 1_000));
+		assert_has_event!(Event::<Test>::KittyLoanOffered(1, 2, 0, 10, 1_000));
+		// AccountID=2 accepts the loan at block 1, it expires at block 11.
+		assert_ok!(KittiesModule::accept_loan(Origin::signed(2), 0));
+		assert_eq!(KittyUser::<Test>::get(0), Some(2));
+		assert_eq!(Owner::<Test>::get(0), Some(1));
+		assert_eq!(Balances::free_balance(2), 10_000_000_000 - 1_000);
+		assert_has_event!(Event::<Test>::KittyLent(1, 2, 0, 11));
+		// The kitty is returned automatically when the loan expires.
+		run_to_block(10);
+		assert_eq!(KittyUser::<Test>::get(0), Some(2));
+		run_to_block(11);
+		assert_eq!(KittyUser::<Test>::get(0), None);
+		assert_eq!(LoanExpiries::<Test>::get(11).len(), 0);
+		assert_has_event!(Event::<Test>::KittyReturned(1, 0));
+	});
+}
+
+#[test]
+fn lend_failed_when_invalid_duration() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_noop!(KittiesModule::lend(Origin::signed(1), 0, 2, 0, 1_000), Error::<Test>::InvalidLoanDuration);
+	});
+}
+
+#[test]
+fn lend_failed_when_borrower_is_owner() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_noop!(KittiesModule::lend(Origin::signed(1), 0, 1, 10, 0), Error::<Test>::BorrowerIsOwner);
+	});
+}
+
+#[test]
+fn transfer_removes_loan_offer() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, offered to AccountID=3.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::lend(Origin::signed(1), 0, 3, 10, 0));
+		assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+		assert_eq!(LoanOffers::<Test>::get(0), None);
+		// The offer doesn't come back when the kitty returns to the lender.
+		assert_ok!(KittiesModule::transfer(Origin::signed(2), 1, 0));
+		assert_noop!(KittiesModule::accept_loan(Origin::signed(3), 0), Error::<Test>::NoLoanOffer);
+
+		// Nor when the kitty is sold and bought back.
+		assert_ok!(KittiesModule::lend(Origin::signed(1), 0, 3, 10, 0));
+		assert_ok!(KittiesModule::sell(Origin::signed(1), 0, Some(1_500)));
+		assert_ok!(KittiesModule::buy(Origin::signed(2), 0));
+		assert_eq!(LoanOffers::<Test>::get(0), None);
+	});
+}
+
+#[test]
+fn accept_loan_failed_when_not_borrower() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, offered to AccountID=2.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::lend(Origin::signed(1), 0, 2, 10, 1_000));
+		// AccountID=3 is not the borrower.
+		assert_noop!(KittiesModule::accept_loan(Origin::signed(3), 0), Error::<Test>::NoLoanOffer);
+	});
+}
+
+#[test]
+fn accept_loan_failed_when_too_many_loans_expiring() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0,1,2, by AccountID =1.
+		for id in 0..3 {
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+			assert_ok!(KittiesModule::lend(Origin::signed(1), id, 2, 10, 0));
+		}
+		// MaxLoansPerBlock is 2 in the mock.
+		assert_ok!(KittiesModule::accept_loan(Origin::signed(2), 0));
+		assert_ok!(KittiesModule::accept_loan(Origin::signed(2), 1));
+		assert_noop!(KittiesModule::accept_loan(Origin::signed(2), 2), Error::<Test>::TooManyLoansExpiring);
+	});
+}
+
+#[test]
+fn lent_kitty_can_not_be_traded() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, lent to AccountID=2.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::sell(Origin::signed(1), 0, Some(1_500)));
+		assert_ok!(KittiesModule::lend(Origin::signed(1), 0, 2, 10, 1_000));
+		assert_ok!(KittiesModule::accept_loan(Origin::signed(2), 0));
+		// Neither the owner nor the borrower can trade it.
+		assert_noop!(KittiesModule::transfer(Origin::signed(1), 3, 0), Error::<Test>::KittyOnLoan);
+		assert_noop!(KittiesModule::transfer(Origin::signed(2), 3, 0), Error::<Test>::NotOwner);
+		assert_noop!(KittiesModule::sell(Origin::signed(1), 0, Some(2_000)), Error::<Test>::KittyOnLoan);
+		assert_noop!(KittiesModule::sell(Origin::signed(2), 0, Some(2_000)), Error::<Test>::NotOwner);
+		assert_noop!(KittiesModule::buy(Origin::signed(3), 0), Error::<Test>::KittyOnLoan);
+	});
+}
+
+#[test]
+fn lent_kitty_can_only_be_bred_by_user() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, lent to AccountID=2.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::lend(Origin::signed(1), 0, 2, 10, 1_000));
+		assert_ok!(KittiesModule::accept_loan(Origin::signed(2), 0));
+		// Prepare kitty index=1, by AccountID =2.
+		assert_ok!(KittiesModule::create(Origin::signed(2)));
+		// The owner can't breed with the lent kitty, the borrower can.
+		assert_noop!(KittiesModule::breed(Origin::signed(1), 0, 1), Error::<Test>::NotKittyUser);
+		assert_ok!(KittiesModule::breed(Origin::signed(2), 0, 1));
+		assert_eq!(Owner::<Test>::get(2), Some(2));
+	});
+}
+
+#[test]
+fn release_works() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, with a name.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::set_kitty_name(Origin::signed(1), 0, b"Tom".to_vec()));
+		assert_ok!(KittiesModule::release(Origin::signed(1), 0));
+		assert!(Kitties::<Test>::get(0).is_none());
+		assert_eq!(Owner::<Test>::get(0), None);
+		assert_eq!(KittyNames::<Test>::get(0), None);
+		// The stake and the deposit are returned.
+		assert_eq!(Balances::reserved_balance(1), 0);
+		// Test the Event emitted already.
+		// KittyReleased(Owner, KittyIndex)
+		assert_has_event!(Event::<Test>::KittyReleased(1, 0));
+	});
+}
+
+#[test]
+fn release_failed_when_not_owner() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_noop!(KittiesModule::release(Origin::signed(2), 0), Error::<Test>::NotOwner);
+	});
+}
+
+#[test]
+fn locked_kitty_can_not_be_traded() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, listed for sale and locked by another pallet.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::sell(Origin::signed(1), 0, Some(1_500)));
+		assert_ok!(<KittiesModule as KittyLock<u32>>::lock(0, *b"kittylck"));
+		assert!(<KittiesModule as KittyLock<u32>>::is_locked(0));
+		assert_has_event!(Event::<Test>::KittyLocked(0, *b"kittylck"));
+
+		assert_noop!(KittiesModule::transfer(Origin::signed(1), 2, 0), Error::<Test>::KittyLocked);
+		assert_noop!(KittiesModule::sell(Origin::signed(1), 0, None), Error::<Test>::KittyLocked);
+		assert_noop!(KittiesModule::buy(Origin::signed(2), 0), Error::<Test>::KittyLocked);
+		assert_noop!(KittiesModule::release(Origin::signed(1), 0), Error::<Test>::KittyLocked);
+		assert_noop!(KittiesModule::lend(Origin::signed(1), 0, 2, 10, 0), Error::<Test>::KittyLocked);
+
+		// It can be traded again after unlocked.
+		assert_ok!(<KittiesModule as KittyLock<u32>>::unlock(0, *b"kittylck"));
+		assert_has_event!(Event::<Test>::KittyUnlocked(0, *b"kittylck"));
+		assert_ok!(KittiesModule::buy(Origin::signed(2), 0));
+	});
+}
+
+#[test]
+fn lock_failed_when_invalid() {
+	new_test_ext().execute_with(|| {
+		// No KittyID =0.
+		assert_noop!(<KittiesModule as KittyLock<u32>>::lock(0, *b"kittylck"), Error::<Test>::InvalidKittyIndex);
+		// Prepare kitty index=0, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_noop!(<KittiesModule as KittyLock<u32>>::unlock(0, *b"kittylck"), Error::<Test>::NotLocked);
+		assert_ok!(<KittiesModule as KittyLock<u32>>::lock(0, *b"kittylck"));
+		assert_noop!(<KittiesModule as KittyLock<u32>>::lock(0, *b"kittylck"), Error::<Test>::AlreadyLocked);
+		// Locks for other reasons are independent.
+		assert_ok!(<KittiesModule as KittyLock<u32>>::lock(0, *b"otherlck"));
+		assert_ok!(<KittiesModule as KittyLock<u32>>::unlock(0, *b"kittylck"));
+		assert!(<KittiesModule as KittyLock<u32>>::is_locked(0));
+	});
+}
+
+#[test]
+fn lock_exclusive_works() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, locked by another pallet.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(<KittiesModule as KittyLock<u32>>::lock(0, *b"otherlck"));
+		// A locked kitty can't be locked exclusively.
+		assert_noop!(<KittiesModule as KittyLock<u32>>::lock_exclusive(0, *b"kittylck"), Error::<Test>::KittyLocked);
+		assert_ok!(<KittiesModule as KittyLock<u32>>::unlock(0, *b"otherlck"));
+
+		// No other lock can be added to an exclusively locked kitty until it's unlocked.
+		assert_ok!(<KittiesModule as KittyLock<u32>>::lock_exclusive(0, *b"kittylck"));
+		assert_has_event!(Event::<Test>::KittyLocked(0, *b"kittylck"));
+		assert_noop!(<KittiesModule as KittyLock<u32>>::lock(0, *b"otherlck"), Error::<Test>::KittyLocked);
+		assert_ok!(<KittiesModule as KittyLock<u32>>::unlock(0, *b"kittylck"));
+		assert_ok!(<KittiesModule as KittyLock<u32>>::lock(0, *b"otherlck"));
+	});
+}
+
+#[test]
+fn admin_calls_failed_when_not_admin() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_noop!(KittiesModule::force_transfer(Origin::signed(1), 2, 0), BadOrigin);
+		assert_noop!(KittiesModule::force_burn(Origin::signed(1), 0), BadOrigin);
+		assert_noop!(KittiesModule::pause_marketplace(Origin::signed(1)), BadOrigin);
+		assert_noop!(KittiesModule::unpause_marketplace(Origin::signed(1)), BadOrigin);
+		assert_noop!(KittiesModule::set_stake_amount(Origin::signed(1), Some(1)), BadOrigin);
+	});
+}
+
+#[test]
+fn force_transfer_works() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, listed for sale.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::sell(Origin::signed(1), 0, Some(1_500)));
+
+		assert_ok!(KittiesModule::force_transfer(Origin::root(), 2, 0));
+		assert_eq!(Owner::<Test>::get(0), Some(2));
+		// The stake is moved to the new owner, and the listing is removed.
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(2), 10_000);
+		assert_eq!(ListForSale::<Test>::get(0), None);
+		assert_has_event!(Event::<Test>::KittyDelisted { owner: 1, kitty_id: 0 });
+		assert_has_event!(Event::<Test>::KittyTransferred(1, 2, 0));
+
+		// No KittyID =1.
+		assert_noop!(KittiesModule::force_transfer(Origin::root(), 2, 1), Error::<Test>::InvalidKittyIndex);
+	});
+}
+
+#[test]
+fn force_transfer_works_without_stake() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+		// AccountID=3 can't pay the stake, so it holds the kitty without stake.
+		assert_ok!(KittiesModule::force_transfer(Origin::root(), 3, 0));
+		assert_eq!(Owner::<Test>::get(0), Some(3));
+		assert_eq!(KittyStake::<Test>::get(0), Some(0));
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(3), 0);
+		assert_has_event!(Event::<Test>::KittyTransferred(1, 3, 0));
+
+		// It trades the kitty like the others.
+		assert_ok!(KittiesModule::transfer(Origin::signed(3), 2, 0));
+		assert_eq!(Balances::reserved_balance(2), 10_000);
+	});
+}
+
+#[test]
+fn force_transfer_failed_when_locked_or_lent() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, locked by another pallet.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(<KittiesModule as KittyLock<u32>>::lock(0, *b"kittylck"));
+		assert_noop!(KittiesModule::force_transfer(Origin::root(), 2, 0), Error::<Test>::KittyLocked);
+
+		// Lent to AccountID=2.
+		assert_ok!(<KittiesModule as KittyLock<u32>>::unlock(0, *b"kittylck"));
+		assert_ok!(KittiesModule::lend(Origin::signed(1), 0, 2, 10, 0));
+		assert_ok!(KittiesModule::accept_loan(Origin::signed(2), 0));
+		assert_noop!(KittiesModule::force_transfer(Origin::root(), 3, 0), Error::<Test>::KittyOnLoan);
+
+		// It moves once the loan expires.
+		run_to_block(11);
+		assert_ok!(KittiesModule::force_transfer(Origin::root(), 3, 0));
+		assert_eq!(Owner::<Test>::get(0), Some(3));
+	});
+}
+
+#[test]
+fn force_transfer_removes_loan_offer() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, offered to AccountID=3.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::lend(Origin::signed(1), 0, 3, 10, 0));
+		assert_ok!(KittiesModule::force_transfer(Origin::root(), 2, 0));
+		assert_eq!(Balances::reserved_balance(2), 10_000);
+		// The borrower can't accept the offer of the ex-owner.
+		assert_noop!(KittiesModule::accept_loan(Origin::signed(3), 0), Error::<Test>::NoLoanOffer);
+	});
+}
+
+#[test]
+fn force_burn_works() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+		assert_ok!(KittiesModule::force_burn(Origin::root(), 0));
+		assert!(Kitties::<Test>::get(0).is_none());
+		assert_eq!(Owner::<Test>::get(0), None);
+		// The stake is returned.
+		assert_eq!(Balances::reserved_balance(1), 0);
+		// KittyBurned(Owner, KittyIndex)
+		assert_has_event!(Event::<Test>::KittyBurned(1, 0));
+	});
+}
+
+#[test]
+fn pause_marketplace_works() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, listed for sale.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::sell(Origin::signed(1), 0, Some(1_500)));
+
+		assert_ok!(KittiesModule::pause_marketplace(Origin::root()));
+		assert_has_event!(Event::<Test>::MarketplacePaused);
+		assert_noop!(KittiesModule::sell(Origin::signed(1), 0, None), Error::<Test>::MarketplaceIsPaused);
+		assert_noop!(KittiesModule::buy(Origin::signed(2), 0), Error::<Test>::MarketplaceIsPaused);
+		// Transferring is not paused.
+		assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+
+		assert_ok!(KittiesModule::unpause_marketplace(Origin::root()));
+		assert_has_event!(Event::<Test>::MarketplaceUnpaused);
+		assert_ok!(KittiesModule::sell(Origin::signed(2), 0, Some(1_500)));
+		assert_ok!(KittiesModule::buy(Origin::signed(1), 0));
+	});
+}
+
+#[test]
+fn set_stake_amount_works() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, with the default stake.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+		assert_ok!(KittiesModule::set_stake_amount(Origin::root(), Some(20_000)));
+		assert_has_event!(Event::<Test>::StakeAmountSet(20_000));
+		// The new kitty is staked with the new amount, while the old one keeps its stake.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_eq!(Balances::reserved_balance(1), 30_000);
+
+		// The new owner stakes the new amount, and the ex-owner gets the old stake back.
+		assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+		assert_eq!(Balances::reserved_balance(1), 20_000);
+		assert_eq!(Balances::reserved_balance(2), 20_000);
+
+		// None falls back to StakeForEachKitty.
+		assert_ok!(KittiesModule::set_stake_amount(Origin::root(), None));
+		assert_has_event!(Event::<Test>::StakeAmountSet(10_000));
+		assert_ok!(KittiesModule::release(Origin::signed(2), 0));
+		assert_eq!(Balances::reserved_balance(2), 0);
+	});
+}
+
+#[test]
+fn set_stake_amount_failed_when_invalid() {
+	new_test_ext().execute_with(|| {
+		// ExistentialDeposit is 500 in the mock.
+		assert_noop!(KittiesModule::set_stake_amount(Origin::root(), Some(0)), Error::<Test>::InvalidStakeAmount);
+		assert_noop!(KittiesModule::set_stake_amount(Origin::root(), Some(499)), Error::<Test>::InvalidStakeAmount);
+		assert_ok!(KittiesModule::set_stake_amount(Origin::root(), Some(500)));
+	});
+}
+
+#[test]
+fn set_call_paused_works() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0 and 1, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_noop!(KittiesModule::set_call_paused(Origin::signed(1), CallKind::Breed, true), BadOrigin);
+
+		assert_ok!(KittiesModule::set_call_paused(Origin::root(), CallKind::Breed, true));
+		assert_has_event!(Event::<Test>::CallPaused(CallKind::Breed, true));
+		assert!(KittiesModule::is_call_paused(CallKind::Breed));
+		assert_noop!(KittiesModule::breed(Origin::signed(1), 0, 1), Error::<Test>::CallIsPaused);
+		// Other calls are not paused.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+		assert_ok!(KittiesModule::set_call_paused(Origin::root(), CallKind::Breed, false));
+		assert_has_event!(Event::<Test>::CallPaused(CallKind::Breed, false));
+		assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+	});
+}
+
+#[test]
+fn mint_phase_closed_works() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(KittiesModule::set_mint_phase(Origin::signed(1), MintPhase::Closed), BadOrigin);
+		assert_ok!(KittiesModule::set_mint_phase(Origin::root(), MintPhase::Closed));
+		assert_has_event!(Event::<Test>::MintPhaseSet(MintPhase::Closed));
+		assert_noop!(KittiesModule::create(Origin::signed(1)), Error::<Test>::MintClosed);
+	});
+}
+
+#[test]
+fn mint_phase_allowlist_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::set_mint_phase(Origin::root(), MintPhase::Allowlist));
+		assert_ok!(KittiesModule::set_mint_allowlist(Origin::root(), 1, 2));
+		assert_has_event!(Event::<Test>::MintAllowlistSet(1, 2));
+
+		// AccountID =1 can mint 2 kitties for free.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_eq!(KittiesModule::mint_allowlist(1), 1);
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_eq!(KittiesModule::mint_allowlist(1), 0);
+		assert_noop!(KittiesModule::create(Origin::signed(1)), Error::<Test>::NotInMintAllowlist);
+		// AccountID =2 is not in the allowlist.
+		assert_noop!(KittiesModule::create(Origin::signed(2)), Error::<Test>::NotInMintAllowlist);
+		assert_eq!(Balances::free_balance(1), 10_000_000_000 - 20_000);
+	});
+}
+
+#[test]
+fn mint_phase_public_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::set_public_mint(Origin::root(), Some(1), 1_000));
+		assert_has_event!(Event::<Test>::PublicMintSet(Some(1), 1_000));
+
+		// The mint price goes to the treasury.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_eq!(Balances::free_balance(1), 10_000_000_000 - 10_000 - 1_000);
+		assert_eq!(Balances::free_balance(100), 1_000);
+		assert_eq!(KittiesModule::public_minted(), 1);
+
+		// The public cap is reached.
+		assert_noop!(KittiesModule::create(Origin::signed(2)), Error::<Test>::PublicMintCapReached);
+		assert_ok!(KittiesModule::set_public_mint(Origin::root(), None, 1_000));
+		assert_ok!(KittiesModule::create(Origin::signed(2)));
+	});
+}
+
+#[test]
+fn create_failed_when_gen0_supply_exhausted() {
+	new_test_ext().execute_with(|| {
+		// MaxGen0Supply = 6.
+		for _ in 0..6 {
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+		}
+		assert_eq!(KittiesModule::gen0_minted(), 6);
+		assert_eq!(KittiesModule::remaining_gen0_supply(), 0);
+		assert_eq!(KittiesModule::remaining_supply(), 2);
+		assert_noop!(KittiesModule::create(Origin::signed(1)), Error::<Test>::Gen0SupplyExhausted);
+
+		// Breeding is still allowed, up to MaxSupply = 8.
+		assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+		assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+		assert_eq!(KittiesModule::bred_minted(), 2);
+		assert_eq!(KittiesModule::remaining_supply(), 0);
+		assert_noop!(KittiesModule::breed(Origin::signed(1), 0, 1), Error::<Test>::SupplyExhausted);
+	});
+}
+
+#[test]
+fn release_keeps_next_kitty_id() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0 and 1, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::release(Origin::signed(1), 0));
+		assert_eq!(NextKittyId::<Test>::get(), 2);
+		assert_eq!(TotalLiveKitties::<Test>::get(), 1);
+		// The id of the released kitty is not reused.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		let dna = Kitties::<Test>::get(2).unwrap().0;
+		assert_has_event!(Event::<Test>::KittyCreated { owner: 1, kitty_id: 2, dna });
+		assert_eq!(TotalLiveKitties::<Test>::get(), 2);
+	});
+}
+
+#[test]
+fn migrate_to_v2_works() {
+	new_test_ext().execute_with(|| {
+		// Prepare the V1 storage: KittiesCount = 3, with the kitty index=1 released,
+		// and the kitty index=2 bred after the supply caps.
+		frame_support::storage::migration::put_storage_value(b"KittiesModule", b"KittiesCount", &[], 3u32);
+		Kitties::<Test>::insert(0, Kitty([0; 16]));
+		Kitties::<Test>::insert(2, Kitty([2; 16]));
+		BredMinted::<Test>::put(1);
+
+		crate::migrations::migrate_to_v2::<Test>();
+		assert_eq!(NextKittyId::<Test>::get(), 3);
+		assert_eq!(TotalLiveKitties::<Test>::get(), 2);
+		// The other minted kitties count as gen-0, against MaxGen0Supply = 6.
+		assert_eq!(KittiesModule::gen0_minted(), 2);
+		assert_eq!(KittiesModule::remaining_gen0_supply(), 4);
+		assert_eq!(KittiesModule::remaining_supply(), 5);
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V2);
+		assert_eq!(
+			frame_support::storage::migration::get_storage_value::<u32>(b"KittiesModule", b"KittiesCount", &[]),
+			None
+		);
+
+		// The migration runs only once.
+		NextKittyId::<Test>::put(5);
+		crate::migrations::migrate_to_v2::<Test>();
+		assert_eq!(NextKittyId::<Test>::get(), 5);
+	});
+}
+
+#[test]
+fn migrate_to_v3_works() {
+	new_test_ext().execute_with(|| {
+		use codec::Encode;
+		use frame_support::storage::migration::{get_storage_value, put_storage_value};
+		// Prepare the V2 storage with the values in Option: kitty index=0 listed, index=1 not for sale.
+		StorageVersion::<Test>::put(Releases::V2);
+		let key = |id: u32| id.using_encoded(|id| sp_io::hashing::blake2_128(id).iter().chain(id).cloned().collect::<Vec<u8>>());
+		for id in 0..2u32 {
+			put_storage_value(b"KittiesModule", b"Kitties", &key(id), Some(Kitty([id as u8; 16])));
+			put_storage_value(b"KittiesModule", b"Owner", &key(id), Some(1u64));
+		}
+		put_storage_value(b"KittiesModule", b"ListForSale", &key(0), Some(1_500u128));
+		put_storage_value(b"KittiesModule", b"ListForSale", &key(1), None::<u128>);
+
+		crate::migrations::migrate_to_v3::<Test>();
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V3);
+		assert_eq!(Kitties::<Test>::get(1).map(|kitty| kitty.0), Some([1; 16]));
+		assert_eq!(Owner::<Test>::get(0), Some(1));
+		assert_eq!(Owner::<Test>::get(1), Some(1));
+		assert_eq!(ListForSale::<Test>::get(0), Some(1_500));
+		assert!(!ListForSale::<Test>::contains_key(1));
+		assert_eq!(get_storage_value::<u128>(b"KittiesModule", b"ListForSale", &key(1)), None);
+	});
+}
+
+#[test]
+fn buy_records_sales() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0 and 1, by AccountID =1, listed for sale.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_eq!(KittiesModule::floor_price(), None);
+		assert_ok!(KittiesModule::sell(Origin::signed(1), 0, Some(2_000)));
+		assert_ok!(KittiesModule::sell(Origin::signed(1), 1, Some(1_500)));
+		assert_eq!(KittiesModule::floor_price(), Some(1_500));
+
+		// Kitty index=0 is sold 3 times, with MaxSalesPerKitty = 2.
+		assert_ok!(KittiesModule::buy(Origin::signed(2), 0));
+		assert_ok!(KittiesModule::sell(Origin::signed(2), 0, Some(2_500)));
+		run_to_block(2);
+		assert_ok!(KittiesModule::buy(Origin::signed(1), 0));
+		assert_ok!(KittiesModule::sell(Origin::signed(1), 0, Some(3_000)));
+		run_to_block(3);
+		assert_ok!(KittiesModule::buy(Origin::signed(2), 0));
+		let sales = KittiesModule::kitty_sales(0);
+		assert_eq!(sales.len(), 2);
+		assert_eq!(sales[0].price, 2_500);
+		assert_eq!(
+			KittiesModule::last_sale(0),
+			Some(SaleRecord { seller: 1, buyer: 2, price: 3_000, block: 3 })
+		);
+		assert_eq!(KittiesModule::last_sale(1), None);
+
+		// The global sales, with MaxRecentSales = 3.
+		assert_ok!(KittiesModule::buy(Origin::signed(2), 1));
+		let recent: Vec<(u32, u128)> = KittiesModule::recent_sales().iter().map(|(id, sale)| (*id, sale.price)).collect();
+		assert_eq!(recent, vec![(0, 2_500), (0, 3_000), (1, 1_500)]);
+		assert_eq!(KittiesModule::floor_price(), None);
+
+		// The sales of a released kitty are removed.
+		assert_ok!(KittiesModule::release(Origin::signed(2), 0));
+		assert!(KittiesModule::kitty_sales(0).is_empty());
+		assert_eq!(KittiesModule::last_sale(0), None);
+	});
+}
+
+#[test]
+fn genesis_config_works() {
+	use frame_support::traits::GenesisBuild;
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 10_000_000_000)],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+	// Kitty index=0 owned by AccountID =1 listed for sale, index=5 owned by AccountID =2 without balance,
+	// bred from index=0 and 3, and index=6 of unknown lineage.
+	GenesisConfig::<Test> {
+		kitties: vec![
+			(0, 1, [1; 16], Some(1_500), Some((None, 0))),
+			(5, 2, [2; 16], None, Some((Some((0, 3)), 2))),
+			(6, 2, [3; 16], None, None),
+		],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	sp_io::TestExternalities::new(storage).execute_with(|| {
+		assert_eq!(Owner::<Test>::get(0), Some(1));
+		assert_eq!(Owner::<Test>::get(5), Some(2));
+		assert_eq!(Kitties::<Test>::get(5).map(|kitty| kitty.0), Some([2; 16]));
+		assert_eq!(ListForSale::<Test>::get(0), Some(1_500));
+		assert_eq!(NextKittyId::<Test>::get(), 7);
+		assert_eq!(TotalLiveKitties::<Test>::get(), 3);
+		// The lineage is kept, and only the gen-0 kitties count as gen-0.
+		assert_eq!(KittiesModule::lineage(5), Some(Lineage { parents: Some((0, 3)), generation: 2 }));
+		assert_eq!(KittiesModule::lineage(6), None);
+		assert_eq!(KittiesModule::gen0_minted(), 1);
+		assert_eq!(KittiesModule::bred_minted(), 2);
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V3);
+		// The stake is reserved if the owner can pay it.
+		assert_eq!(Balances::reserved_balance(1), 10_000);
+		assert_eq!(KittiesModule::kitty_stake(5), 0);
+	});
+}
+
+#[test]
+fn genesis_config_works_over_gen0_supply() {
+	use frame_support::traits::GenesisBuild;
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	// MaxGen0Supply is 6 and MaxSupply 8 in the mock.
+	GenesisConfig::<Test> {
+		kitties: (0..7).map(|id| (id, 1, [id as u8; 16], None, Some((None, 0)))).collect(),
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	sp_io::TestExternalities::new(storage).execute_with(|| {
+		assert_eq!(KittiesModule::gen0_minted(), 7);
+		assert_eq!(KittiesModule::remaining_gen0_supply(), 0);
+		assert_eq!(KittiesModule::remaining_supply(), 1);
+	});
+}
+
+#[test]
+#[should_panic(expected = "More kitties in genesis than MaxSupply")]
+fn genesis_config_failed_when_over_supply() {
+	use frame_support::traits::GenesisBuild;
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	// MaxSupply is 8 in the mock.
+	GenesisConfig::<Test> {
+		kitties: (0..9).map(|id| (id, 1, [id as u8; 16], None, None)).collect(),
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+}
+
+#[test]
+fn genes_from_dna_works() {
+	use crate::genes::*;
+	let dna = [10, 20, 30, 40, 50, 60, 70, 80, 90, 5, 7, 2, 4, 200, 0, 0];
+	assert_eq!(Kitty(dna).genes(), Genes {
+		body_color: [10, 20, 30],
+		pattern_color: [40, 50, 60],
+		eye_color: [70, 80, 90],
+		pattern: Pattern::Stripes,
+		eye_shape: EyeShape::Wide,
+		ear_shape: EarShape::Folded,
+		mouth_shape: MouthShape::Flat,
+		background_hue: 200,
+	});
+}
+
+#[test]
+fn breed_records_lineage() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+		assert_ok!(KittiesModule::breed(Origin::signed(1), 2, 0));
+		assert_eq!(KittiesModule::lineage(0), Some(Lineage { parents: None, generation: 0 }));
+		assert_eq!(KittiesModule::lineage(2), Some(Lineage { parents: Some((0, 1)), generation: 1 }));
+		assert_eq!(KittiesModule::lineage(3), Some(Lineage { parents: Some((2, 0)), generation: 2 }));
+
+		assert_ok!(KittiesModule::release(Origin::signed(1), 3));
+		assert!(!KittyLineage::<Test>::contains_key(3));
+	});
+}
+
+#[test]
+fn lineage_unknown_for_kitties_before_it() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		// Kitty 0 was minted before the lineage was recorded.
+		KittyLineage::<Test>::remove(0);
+
+		assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+		assert_eq!(KittiesModule::lineage(0), None);
+		assert_eq!(KittiesModule::lineage(2), None);
+
+		let dna = KittiesModule::kitties(0).unwrap().0;
+		let json = String::from_utf8(crate::metadata::metadata_json(0, &dna, None)).unwrap();
+		assert!(json.contains(r#""generation":null,"parents":null"#));
+		assert!(!json.contains("Generation"));
+	});
+}
+
+#[test]
+fn offchain_worker_caches_metadata() {
+	use frame_support::traits::Hooks;
+	use sp_core::offchain::{testing::TestOffchainExt, OffchainDbExt, OffchainWorkerExt};
+
+	let mut ext = new_test_ext();
+	let (offchain, _state) = TestOffchainExt::new();
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	ext.execute_with(|| {
+		let metadata = |kitty_id: u32| crate::metadata::cached_metadata(&kitty_id)
+			.map(|cached| String::from_utf8(cached.json).unwrap());
+
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		KittiesModule::offchain_worker(1);
+		assert!(metadata(0).unwrap().contains(r#""name":"Kitty #0""#));
+		assert!(metadata(1).unwrap().contains(r#""generation":0,"parents":null"#));
+
+		// The next run caches the new kitties only.
+		assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+		KittiesModule::offchain_worker(2);
+		let dna = KittiesModule::kitties(2).unwrap().0;
+		assert_eq!(
+			metadata(2).unwrap(),
+			String::from_utf8(crate::metadata::metadata_json(2, &dna, KittiesModule::lineage(2).as_ref())).unwrap(),
+		);
+		assert_eq!(crate::metadata::cached_metadata(&2u32).unwrap().dna, dna);
+		assert!(metadata(2).unwrap().contains(r#""generation":1,"parents":[0,1]"#));
+		assert!(metadata(2).unwrap().contains(r#"{"display_type":"number","trait_type":"Generation","value":1}]}"#));
+		assert_eq!(metadata(3), None);
+	});
+}
+
+#[test]
+fn offchain_worker_caches_metadata_again_after_reorg() {
+	use frame_support::traits::Hooks;
+	use sp_core::offchain::{testing::TestOffchainExt, OffchainDbExt, OffchainWorkerExt};
+
+	let mut ext = new_test_ext();
+	let (offchain, _state) = TestOffchainExt::new();
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	ext.execute_with(|| {
+		let cached_dna = |kitty_id: u32| crate::metadata::cached_metadata(&kitty_id).map(|cached| cached.dna);
+
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		KittiesModule::offchain_worker(1);
+		let dna_0 = KittiesModule::kitties(0).unwrap().0;
+		assert_eq!(cached_dna(1), Some(KittiesModule::kitties(1).unwrap().0));
+
+		// On the new best chain, kitty 1 is another kitty, and kitty 2 is new.
+		Kitties::<Test>::insert(1, Kitty([7; 16]));
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		KittiesModule::offchain_worker(2);
+		assert_eq!(cached_dna(0), Some(dna_0));
+		assert_eq!(cached_dna(1), Some([7; 16]));
+		assert_eq!(cached_dna(2), Some(KittiesModule::kitties(2).unwrap().0));
+	});
+}
+
+#[test]
+fn claim_starter_kitty_works() {
+	use codec::Encode;
+	use sp_runtime::testing::TestSignature;
+	new_test_ext().execute_with(|| {
+		// AccountID =9 issues a voucher for AccountID =4, which has no balance.
+		assert_ok!(KittiesModule::set_voucher_issuer(Origin::root(), Some(9)));
+		assert_has_event!(Event::<Test>::VoucherIssuerSet(Some(9)));
+		let voucher = StarterVoucher { who: 4, expiry: 10 };
+		let signature = TestSignature(9, voucher.encode());
+
+		assert_noop!(
+			KittiesModule::claim_starter_kitty(Origin::signed(4), voucher.clone(), signature.clone()),
+			BadOrigin
+		);
+		assert_ok!(KittiesModule::claim_starter_kitty(Origin::none(), voucher.clone(), signature.clone()));
+		assert_eq!(Owner::<Test>::get(0), Some(4));
+		assert_eq!(KittiesModule::kitty_stake(0), 0);
+		assert_eq!(KittiesModule::gen0_minted(), 1);
+		assert_eq!(KittiesModule::mints_in_current_era(&4), 1);
+		assert_has_event!(Event::<Test>::KittyCreated { owner: 4, kitty_id: 0, dna: KittiesModule::kitties(0).unwrap().0 });
+
+		// Each account claims once.
+		assert_noop!(
+			KittiesModule::claim_starter_kitty(Origin::none(), voucher, signature),
+			Error::<Test>::StarterKittyAlreadyClaimed
+		);
+	});
+}
+
+#[test]
+fn claim_starter_kitty_failed_when_invalid_voucher() {
+	use codec::Encode;
+	use sp_runtime::testing::TestSignature;
+	new_test_ext().execute_with(|| {
+		let voucher = StarterVoucher { who: 4, expiry: 10 };
+		assert_noop!(
+			KittiesModule::claim_starter_kitty(Origin::none(), voucher.clone(), TestSignature(9, voucher.encode())),
+			Error::<Test>::NoVoucherIssuer
+		);
+
+		assert_ok!(KittiesModule::set_voucher_issuer(Origin::root(), Some(9)));
+		// Not signed by the issuer.
+		assert_noop!(
+			KittiesModule::claim_starter_kitty(Origin::none(), voucher.clone(), TestSignature(4, voucher.encode())),
+			Error::<Test>::InvalidVoucher
+		);
+		// Signed for another account.
+		let other = StarterVoucher { who: 5, ..voucher.clone() };
+		assert_noop!(
+			KittiesModule::claim_starter_kitty(Origin::none(), voucher.clone(), TestSignature(9, other.encode())),
+			Error::<Test>::InvalidVoucher
+		);
+		run_to_block(11);
+		assert_noop!(
+			KittiesModule::claim_starter_kitty(Origin::none(), voucher.clone(), TestSignature(9, voucher.encode())),
+			Error::<Test>::VoucherExpired
+		);
+	});
+}
+
+#[test]
+fn claim_starter_kitty_failed_when_mint_closed_or_exhausted() {
+	use codec::Encode;
+	use frame_support::unsigned::ValidateUnsigned;
+	use sp_runtime::{testing::TestSignature, transaction_validity::{InvalidTransaction, TransactionSource}};
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::set_voucher_issuer(Origin::root(), Some(9)));
+		let voucher = StarterVoucher { who: 4, expiry: 10 };
+		let signature = TestSignature(9, voucher.encode());
+		let validate = || KittiesModule::validate_unsigned(
+			TransactionSource::External,
+			&Call::claim_starter_kitty(voucher.clone(), signature.clone()),
+		);
+		let unavailable = Err(InvalidTransaction::Custom(STARTER_MINT_UNAVAILABLE).into());
+
+		// The vouchers don't open a closed mint.
+		assert_ok!(KittiesModule::set_mint_phase(Origin::root(), MintPhase::Closed));
+		assert_eq!(validate(), unavailable);
+		assert_noop!(
+			KittiesModule::claim_starter_kitty(Origin::none(), voucher.clone(), signature.clone()),
+			Error::<Test>::MintClosed
+		);
+
+		// MaxGen0Supply is 6 in the mock.
+		assert_ok!(KittiesModule::set_mint_phase(Origin::root(), MintPhase::Public));
+		for _ in 0..6 {
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+		}
+		assert_eq!(validate(), unavailable);
+		assert_noop!(
+			KittiesModule::claim_starter_kitty(Origin::none(), voucher.clone(), signature.clone()),
+			Error::<Test>::Gen0SupplyExhausted
+		);
+	});
+}
+
+#[test]
+fn claim_starter_kitty_validate_unsigned_works() {
+	use codec::Encode;
+	use frame_support::unsigned::ValidateUnsigned;
+	use sp_runtime::{testing::TestSignature, transaction_validity::{InvalidTransaction, TransactionSource}};
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::set_voucher_issuer(Origin::root(), Some(9)));
+		let voucher = StarterVoucher { who: 4, expiry: 10 };
+		let validate = |signer| KittiesModule::validate_unsigned(
+			TransactionSource::External,
+			&Call::claim_starter_kitty(voucher.clone(), TestSignature(signer, voucher.encode())),
+		);
+
+		let valid = validate(9).unwrap();
+		assert_eq!(valid.provides, vec![("KittiesStarter", 4u64).encode()]);
+		assert_eq!(valid.longevity, 9);
+		assert_eq!(validate(4), Err(InvalidTransaction::BadProof.into()));
+
+		assert_ok!(KittiesModule::claim_starter_kitty(Origin::none(), voucher.clone(), TestSignature(9, voucher.encode())));
+		assert_eq!(validate(9), Err(InvalidTransaction::Stale.into()));
+		assert_eq!(
+			KittiesModule::validate_unsigned(TransactionSource::External, &Call::create()),
+			Err(InvalidTransaction::Call.into())
+		);
+	});
+}
+
+#[test]
+fn check_kitty_rate_limit_works() {
+	use crate::{mock::Call as RuntimeCall, rate_limit::MINT_RATE_LIMITED};
+	use frame_support::weights::DispatchInfo;
+	use sp_runtime::{traits::SignedExtension, transaction_validity::InvalidTransaction};
+	new_test_ext().execute_with(|| {
+		let check = |who: u64, call: Call<Test>| CheckKittyRateLimit::<Test>::new()
+			.validate(&who, &RuntimeCall::KittiesModule(call), &DispatchInfo::default(), 0);
+
+		// MaxMintsPerEra = 3, in the eras of 10 blocks.
+		for _ in 0..2 {
+			assert_ok!(check(1, Call::create()));
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+		}
+		assert_ok!(check(1, Call::breed(0, 1)));
+		assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+		assert_eq!(KittiesModule::mints_in_current_era(&1), 3);
+		let limited = Err(InvalidTransaction::Custom(MINT_RATE_LIMITED).into());
+		assert_eq!(check(1, Call::create()), limited);
+		assert_eq!(check(1, Call::breed(0, 1)), limited);
+
+		// The other calls and accounts are not limited.
+		assert_ok!(check(1, Call::transfer(2, 0)));
+		assert_ok!(check(2, Call::create()));
+
+		// The count restarts in the next era.
+		run_to_block(10);
+		assert_eq!(KittiesModule::mints_in_current_era(&1), 0);
+		assert_ok!(check(1, Call::create()));
+	});
+}
+
+#[test]
+fn check_kitty_rate_limit_provides_mint_slot() {
+	use crate::mock::Call as RuntimeCall;
+	use codec::Encode;
+	use frame_support::weights::DispatchInfo;
+	use sp_runtime::traits::SignedExtension;
+	new_test_ext().execute_with(|| {
+		let check = |who: u64, call: Call<Test>| CheckKittyRateLimit::<Test>::new()
+			.validate(&who, &RuntimeCall::KittiesModule(call), &DispatchInfo::default(), 0)
+			.unwrap();
+		let tag = |who: u64, era: u64, slot: u32| ("KittyMint", (who, era, slot)).encode();
+
+		// The pending mints of an account take the same slot, so the pool keeps one of them.
+		assert_eq!(check(1, Call::create()).provides, vec![tag(1, 0, 0)]);
+		assert_eq!(check(1, Call::create()).provides, vec![tag(1, 0, 0)]);
+		assert_eq!(check(1, Call::breed(0, 1)).provides, vec![tag(1, 0, 0)]);
+		assert_eq!(check(2, Call::create()).provides, vec![tag(2, 0, 0)]);
+		// They're revalidated in the next era, at block 10.
+		assert_eq!(check(1, Call::create()).longevity, 9);
+
+		// The next mint takes the next slot, once the first one is included.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_eq!(check(1, Call::create()).provides, vec![tag(1, 0, 1)]);
+
+		// The other calls provide no tag.
+		assert!(check(1, Call::transfer(2, 0)).provides.is_empty());
+
+		run_to_block(10);
+		assert_eq!(check(1, Call::create()).provides, vec![tag(1, 1, 0)]);
+		assert_eq!(check(1, Call::create()).longevity, 10);
+	});
+}
+
+#[test]
+fn integrity_test_works() {
+	use frame_support::traits::IntegrityTest;
+	new_test_ext().execute_with(|| {
+		KittiesModule::integrity_test();
+	});
+}
+
+#[test]
+fn do_try_state_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::do_try_state());
+
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+		assert_ok!(KittiesModule::sell(Origin::signed(1), 2, Some(1_000)));
+		assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+		assert_ok!(KittiesModule::do_try_state());
+
+		// A kitty without owner, and a listing without kitty.
+		Owner::<Test>::remove(1);
+		assert_eq!(KittiesModule::do_try_state(), Err("A kitty has no owner"));
+		Owner::<Test>::insert(1, 1);
+		ListForSale::<Test>::insert(7, 1_000);
+		assert_eq!(KittiesModule::do_try_state(), Err("A listing has no kitty"));
+		ListForSale::<Test>::remove(7);
+		TotalLiveKitties::<Test>::put(2);
+		assert_eq!(KittiesModule::do_try_state(), Err("TotalLiveKitties is not the count of the kitties"));
+	});
+}
+
+mod invariants {
+	use crate::fuzzing::{run, Op};
+	use proptest::prelude::*;
+
+	fn op() -> impl Strategy<Value = Op> {
+		prop_oneof![
+			any::<u8>().prop_map(|who| Op::Create { who }),
+			any::<(u8, u8, u8)>().prop_map(|(who, kitty_1, kitty_2)| Op::Breed { who, kitty_1, kitty_2 }),
+			any::<(u8, u8, u8)>().prop_map(|(who, to, kitty)| Op::Transfer { who, to, kitty }),
+			any::<(u8, u8, Option<u16>)>().prop_map(|(who, kitty, price)| Op::Sell { who, kitty, price }),
+			any::<(u8, u8)>().prop_map(|(who, kitty)| Op::Buy { who, kitty }),
+			Just(Op::NextEra),
+		]
+	}
+
+	proptest! {
+		#[test]
+		fn invariants_hold_after_random_calls(ops in prop::collection::vec(op(), 1..50)) {
+			run(&ops);
+		}
+	}
+}
@@ -0,0 +1,33 @@
+//! Traits for other pallets to use the kitties.
+
+use frame_support::{dispatch::DispatchResult, traits::LockIdentifier};
+
+/// Lock a kitty for other pallets (e.g. games, staking or escrow) without taking its ownership.
+/// A locked kitty can't be transferred, sold, bought, lent or released.
+pub trait KittyLock<KittyIndex> {
+	/// Lock a kitty for `reason`. A kitty can be locked for several reasons at the same time.
+	fn lock(kitty_id: KittyIndex, reason: LockIdentifier) -> DispatchResult;
+	/// Lock a kitty for `reason` only, e.g. to hold it in escrow. It fails if the kitty is locked
+	/// or on loan, and no other lock can be added until it's unlocked.
+	fn lock_exclusive(kitty_id: KittyIndex, reason: LockIdentifier) -> DispatchResult;
+	/// Remove the lock of `reason` from a kitty.
+	fn unlock(kitty_id: KittyIndex, reason: LockIdentifier) -> DispatchResult;
+	/// Whether a kitty is locked for any reason.
+	fn is_locked(kitty_id: KittyIndex) -> bool;
+}
+
+/// Read the kitties for other pallets.
+pub trait KittyInspect<AccountId, KittyIndex> {
+	/// The owner of a kitty, None if it doesn't exist.
+	fn owner_of(kitty_id: KittyIndex) -> Option<AccountId>;
+	/// The DNA of a kitty, None if it doesn't exist.
+	fn dna_of(kitty_id: KittyIndex) -> Option<[u8; 16]>;
+	/// Whether a kitty is lent out, for its borrower to use until the loan expires.
+	fn is_lent(kitty_id: KittyIndex) -> bool;
+}
+
+/// Transfer the kitties for other pallets, e.g. to hand over a kitty held in escrow.
+pub trait KittyTransfer<AccountId, KittyIndex> {
+	/// Transfer a kitty with its stake, as the `transfer` call of its owner `from` does.
+	fn transfer_kitty(from: &AccountId, to: &AccountId, kitty_id: KittyIndex) -> DispatchResult;
+}
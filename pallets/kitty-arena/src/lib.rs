@@ -0,0 +1,302 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{
+        dispatch::DispatchResult,
+        pallet_prelude::*,
+        traits::{Currency, ExistenceRequirement, LockIdentifier, Randomness, ReservableCurrency},
+        transactional,
+        PalletId,
+    };
+	use frame_system::pallet_prelude::*;
+    use codec::{Encode, Decode};
+    use sp_io::hashing::blake2_128;
+    use sp_runtime::traits::{AccountIdConversion, Saturating, Zero};
+    use sp_std::prelude::*;
+    use pallet_kitties::traits::{KittyInspect, KittyLock};
+
+    /// The lock held on the kitties entered into a tournament in pallet_kitties.
+    /// A kitty can be in one tournament at a time.
+    pub const ARENA_LOCK_ID: LockIdentifier = *b"kittyarn";
+
+    type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+    /// The stats of a kitty for battles, derived from its DNA.
+    /// Each stat is the sum of 4 genes, from 0 to 1020.
+    #[derive(Encode, Decode, Clone, Copy, PartialEq, RuntimeDebug)]
+    pub struct Stats {
+        pub attack: u32,
+        pub defense: u32,
+        pub speed: u32,
+        pub luck: u32,
+    }
+
+    impl Stats {
+        pub fn from_dna(dna: &[u8; 16]) -> Self {
+            let stat = |i: usize| dna[i..i + 4].iter().map(|gene| *gene as u32).sum::<u32>();
+            Stats { attack: stat(0), defense: stat(4), speed: stat(8), luck: stat(12) }
+        }
+
+        /// The score in a match. `roll` is a random byte, which scales the luck.
+        pub fn score(&self, roll: u8) -> u32 {
+            self.attack + self.defense + self.speed + self.luck * roll as u32 / 255
+        }
+    }
+
+    /// A tournament, whose matches are resolved at the `start` block.
+    /// The `deposit` of the creator is returned then.
+    #[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
+    pub struct Tournament<AccountId, Balance, BlockNumber, Entrants> {
+        pub creator: AccountId,
+        pub deposit: Balance,
+        pub entry_fee: Balance,
+        pub start: BlockNumber,
+        pub entrants: Entrants,
+    }
+
+    type TournamentOf<T> = Tournament<
+        <T as frame_system::Config>::AccountId,
+        BalanceOf<T>,
+        <T as frame_system::Config>::BlockNumber,
+        BoundedVec<(<T as frame_system::Config>::AccountId, <T as pallet_kitties::Config>::KittyIndex), <T as Config>::MaxEntrants>,
+    >;
+
+    #[pallet::config]
+	pub trait Config: frame_system::Config + pallet_kitties::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+        // The currency of the entry fees and the deposits.
+        type Currency: ReservableCurrency<Self::AccountId>;
+        // The kitties pallet, to check the owners and lock the entered kitties.
+        type Kitties: KittyInspect<Self::AccountId, Self::KittyIndex> + KittyLock<Self::KittyIndex>;
+        type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
+        // Maximum number of kitties in a tournament.
+        #[pallet::constant]
+        type MaxEntrants: Get<u32>;
+        // Maximum number of tournaments which start at the same block.
+        #[pallet::constant]
+        type MaxTournamentsPerBlock: Get<u32>;
+        // The amount reserved from the creator of a tournament until it's resolved,
+        // so the tournament slots of a block can't be filled for free.
+        #[pallet::constant]
+        type TournamentDeposit: Get<BalanceOf<Self>>;
+        // The id of the pot accounts, which hold the entry fees of each tournament.
+        #[pallet::constant]
+        type PalletId: Get<PalletId>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+    #[pallet::event]
+	#[pallet::metadata(T::AccountId = "AccountId")]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+        /// A tournament was created. [creator, tournament_id, entry_fee, start]
+        TournamentCreated(T::AccountId, u32, BalanceOf<T>, T::BlockNumber),
+        /// A kitty entered a tournament. [owner, tournament_id, kitty_id]
+        KittyEntered(T::AccountId, u32, T::KittyIndex),
+        /// A match was resolved. [tournament_id, winner_kitty_id, loser_kitty_id]
+        MatchResolved(u32, T::KittyIndex, T::KittyIndex),
+        /// A tournament was resolved and the winners shared the entry fees. [tournament_id, winners, prize]
+        TournamentResolved(u32, u32, BalanceOf<T>),
+        /// A winner couldn't take its prize, e.g. below the existential deposit. The prize goes to
+        /// the next winner, or to the creator after the last one. [tournament_id, winner, amount]
+        PrizeUnpaid(u32, T::AccountId, BalanceOf<T>),
+	}
+
+    /// Storage for the id of the next tournament.
+    #[pallet::storage]
+	#[pallet::getter(fn next_tournament_id)]
+	pub type NextTournamentId<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Storage for the tournaments waiting to start.
+    #[pallet::storage]
+	#[pallet::getter(fn tournaments)]
+	pub type Tournaments<T: Config> = StorageMap<_, Blake2_128Concat, u32, TournamentOf<T>>;
+
+    /// Storage for the tournaments to be resolved at each block, processed in `on_initialize`.
+    #[pallet::storage]
+	#[pallet::getter(fn tournaments_at)]
+	pub type TournamentsAt<T: Config> = StorageMap<
+        _, Twox64Concat, T::BlockNumber, BoundedVec<u32, T::MaxTournamentsPerBlock>, ValueQuery
+    >;
+
+	#[pallet::error]
+	pub enum Error<T> {
+        NotOwner,
+        InvalidTournament,
+        InvalidStart,
+        EntryFeeTooLow,
+        TooManyTournaments,
+        TournamentStarted,
+        TournamentFull,
+        TournamentIdOverflow,
+        NotEnoughBalanceForDeposit,
+	}
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        /// Resolve the tournaments which start at this block.
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            let mut weight = T::DbWeight::get().reads_writes(1, 1);
+
+            for tournament_id in TournamentsAt::<T>::take(n).into_iter() {
+                if let Some(tournament) = Tournaments::<T>::take(tournament_id) {
+                    let entrants = tournament.entrants.len() as Weight;
+                    weight = weight.saturating_add(T::DbWeight::get().reads_writes(1 + 3 * entrants, 1 + 3 * entrants));
+                    Self::resolve(tournament_id, tournament);
+                }
+            }
+
+            weight
+        }
+    }
+
+	#[pallet::call]
+	impl<T:Config> Pallet<T> {
+
+        /// Create a tournament which starts at the `start` block, reserving `TournamentDeposit`
+        /// until it's resolved. The entry fee must be zero or at least the existential deposit.
+        #[pallet::weight(1_000)]
+        pub fn create_tournament(origin: OriginFor<T>, entry_fee: BalanceOf<T>, start: T::BlockNumber) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(start > frame_system::Pallet::<T>::block_number(), Error::<T>::InvalidStart);
+            ensure!(
+                entry_fee.is_zero() || entry_fee >= <T as Config>::Currency::minimum_balance(),
+                Error::<T>::EntryFeeTooLow
+            );
+
+            let tournament_id = Self::next_tournament_id();
+            let next_id = tournament_id.checked_add(1).ok_or(Error::<T>::TournamentIdOverflow)?;
+
+            TournamentsAt::<T>::try_mutate(start, |ids| ids.try_push(tournament_id))
+                .map_err(|_| Error::<T>::TooManyTournaments)?;
+            let deposit = T::TournamentDeposit::get();
+            <T as Config>::Currency::reserve(&who, deposit)
+                .map_err(|_| Error::<T>::NotEnoughBalanceForDeposit)?;
+            Tournaments::<T>::insert(tournament_id, Tournament {
+                creator: who.clone(),
+                deposit,
+                entry_fee,
+                start,
+                entrants: Default::default(),
+            });
+            NextTournamentId::<T>::put(next_id);
+
+            Self::deposit_event(Event::TournamentCreated(who, tournament_id, entry_fee, start));
+
+            Ok(())
+        }
+
+        /// Enter a kitty into a tournament, paying the entry fee.
+        /// The kitty is locked in pallet_kitties until the tournament is resolved.
+        #[pallet::weight(1_000)]
+        #[transactional]
+        pub fn enter(origin: OriginFor<T>, tournament_id: u32, kitty_id: T::KittyIndex) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            // Ensure only the kitty owner can enter it.
+            ensure!(Some(who.clone()) == T::Kitties::owner_of(kitty_id), Error::<T>::NotOwner);
+
+            Tournaments::<T>::try_mutate(tournament_id, |t| -> DispatchResult {
+                let tournament = t.as_mut().ok_or(Error::<T>::InvalidTournament)?;
+                ensure!(tournament.start > frame_system::Pallet::<T>::block_number(), Error::<T>::TournamentStarted);
+                tournament.entrants.try_push((who.clone(), kitty_id))
+                    .map_err(|_| Error::<T>::TournamentFull)?;
+
+                T::Kitties::lock(kitty_id, ARENA_LOCK_ID)?;
+                if !tournament.entry_fee.is_zero() {
+                    <T as Config>::Currency::transfer(
+                        &who,
+                        &Self::pot_account(tournament_id),
+                        tournament.entry_fee,
+                        ExistenceRequirement::KeepAlive,
+                    )?;
+                }
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::KittyEntered(who, tournament_id, kitty_id));
+
+            Ok(())
+        }
+    }
+
+    // Helper functions.
+    impl<T: Config> Pallet<T> {
+        /// The account which holds the entry fees of a tournament.
+        pub fn pot_account(tournament_id: u32) -> T::AccountId {
+            T::PalletId::get().into_sub_account(tournament_id)
+        }
+
+        /// Whether the first kitty wins a match. A tie goes to the faster one, then the first one.
+        pub fn fight(a: &Stats, roll_a: u8, b: &Stats, roll_b: u8) -> bool {
+            let (score_a, score_b) = (a.score(roll_a), b.score(roll_b));
+            score_a > score_b || (score_a == score_b && a.speed >= b.speed)
+        }
+
+        // Pair the entrants in the order of entry and fight the matches.
+        // The last entrant of an odd number gets a bye. The winners share the pot equally,
+        // and the last winner also gets the remainder so the pot is emptied.
+        // A prize which can't be paid goes to the next winner, and after the last one to the creator.
+        fn resolve(tournament_id: u32, tournament: TournamentOf<T>) {
+            let seed = <T as Config>::Randomness::random(&(ARENA_LOCK_ID, tournament_id).encode()).0;
+            let roll = |i: usize| (seed, i as u32).using_encoded(blake2_128)[0];
+            let stats = |kitty_id: T::KittyIndex| {
+                T::Kitties::dna_of(kitty_id).map(|dna| Stats::from_dna(&dna)).unwrap_or(Stats::from_dna(&[0; 16]))
+            };
+
+            let entrants = tournament.entrants.into_inner();
+            let mut winners = Vec::with_capacity(entrants.len() / 2 + 1);
+            for (i, pair) in entrants.chunks(2).enumerate() {
+                match pair {
+                    [a, b] => {
+                        let a_wins = Self::fight(&stats(a.1), roll(2 * i), &stats(b.1), roll(2 * i + 1));
+                        let (winner, loser) = if a_wins { (a, b) } else { (b, a) };
+                        Self::deposit_event(Event::MatchResolved(tournament_id, winner.1, loser.1));
+                        winners.push(winner.0.clone());
+                    },
+                    [bye] => winners.push(bye.0.clone()),
+                    _ => {},
+                }
+            }
+
+            for (_, kitty_id) in entrants.iter() {
+                let _ = T::Kitties::unlock(*kitty_id, ARENA_LOCK_ID);
+            }
+
+            let pot = Self::pot_account(tournament_id);
+            let prize = <T as Config>::Currency::free_balance(&pot) / (winners.len().max(1) as u32).into();
+            let mut unpaid = Zero::zero();
+            for (i, winner) in winners.iter().enumerate() {
+                let amount = if i + 1 == winners.len() {
+                    <T as Config>::Currency::free_balance(&pot)
+                } else {
+                    prize.saturating_add(unpaid)
+                };
+                unpaid = match <T as Config>::Currency::transfer(&pot, winner, amount, ExistenceRequirement::AllowDeath) {
+                    Ok(()) => Zero::zero(),
+                    Err(_) => {
+                        Self::deposit_event(Event::PrizeUnpaid(tournament_id, winner.clone(), amount));
+                        amount
+                    },
+                };
+            }
+            if !unpaid.is_zero() {
+                let _ = <T as Config>::Currency::transfer(&pot, &tournament.creator, unpaid, ExistenceRequirement::AllowDeath);
+            }
+            <T as Config>::Currency::unreserve(&tournament.creator, tournament.deposit);
+
+            Self::deposit_event(Event::TournamentResolved(tournament_id, winners.len() as u32, prize));
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use crate::mock::{Event as TestEvent, new_test_ext, run_to_block, Balances, KittiesModule, KittyArena, Origin, System, Test};
+use frame_support::{assert_ok, assert_noop};
+use pallet_kitties::traits::KittyLock;
+use super::*;
+
+#[test]
+fn stats_from_dna_works() {
+	let mut dna = [0u8; 16];
+	dna[0] = 10;
+	dna[3] = 20;
+	dna[5] = 255;
+	dna[11] = 1;
+	dna[15] = 7;
+	assert_eq!(Stats::from_dna(&dna), Stats { attack: 30, defense: 255, speed: 1, luck: 7 });
+}
+
+#[test]
+fn fight_works() {
+	let strong = Stats { attack: 100, defense: 100, speed: 100, luck: 0 };
+	let lucky = Stats { attack: 50, defense: 50, speed: 50, luck: 1020 };
+	// Without luck, the strong one wins.
+	assert!(KittyArena::fight(&strong, 255, &lucky, 0));
+	// With the best roll, the lucky one wins.
+	assert!(!KittyArena::fight(&strong, 255, &lucky, 255));
+	// A tie goes to the faster one, then the first one.
+	let fast = Stats { attack: 0, defense: 0, speed: 300, luck: 0 };
+	assert!(!KittyArena::fight(&strong, 0, &fast, 0));
+	assert!(KittyArena::fight(&strong, 0, &strong, 0));
+}
+
+#[test]
+fn create_tournament_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyArena::create_tournament(Origin::signed(1), 1_000, 10));
+		assert_eq!(NextTournamentId::<Test>::get(), 1);
+		assert_eq!(TournamentsAt::<Test>::get(10).into_inner(), vec![0]);
+		assert_eq!(Tournaments::<Test>::get(0).unwrap().entry_fee, 1_000);
+		// TournamentDeposit is 1_000 in the mock.
+		assert_eq!(Tournaments::<Test>::get(0).unwrap().deposit, 1_000);
+		assert_eq!(Balances::reserved_balance(1), 1_000);
+		// Test the Event emitted already.
+		// TournamentCreated(Creator, TournamentId, EntryFee, Start)
+		assert_has_event!(Event::<Test>::TournamentCreated(1, 0, 1_000, 10));
+	});
+}
+
+#[test]
+fn create_tournament_failed_when_invalid() {
+	new_test_ext().execute_with(|| {
+		// The start block has passed.
+		assert_noop!(KittyArena::create_tournament(Origin::signed(1), 1_000, 1), Error::<Test>::InvalidStart);
+		// The existential deposit is 500 in the mock.
+		assert_noop!(KittyArena::create_tournament(Origin::signed(1), 100, 10), Error::<Test>::EntryFeeTooLow);
+		// MaxTournamentsPerBlock is 2 in the mock.
+		assert_ok!(KittyArena::create_tournament(Origin::signed(1), 0, 10));
+		assert_ok!(KittyArena::create_tournament(Origin::signed(1), 0, 10));
+		assert_noop!(KittyArena::create_tournament(Origin::signed(1), 0, 10), Error::<Test>::TooManyTournaments);
+		// AccountID =4 has no balance for the deposit.
+		assert_noop!(
+			KittyArena::create_tournament(Origin::signed(4), 0, 11),
+			Error::<Test>::NotEnoughBalanceForDeposit
+		);
+	});
+}
+
+#[test]
+fn enter_works() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittyArena::create_tournament(Origin::signed(2), 1_000, 10));
+		assert_ok!(KittyArena::enter(Origin::signed(1), 0, 0));
+		// The fee is paid into the pot and the kitty is locked.
+		assert_eq!(Balances::free_balance(KittyArena::pot_account(0)), 1_000);
+		assert!(<KittiesModule as KittyLock<u32>>::is_locked(0));
+		assert_has_event!(Event::<Test>::KittyEntered(1, 0, 0));
+	});
+}
+
+#[test]
+fn enter_failed_when_invalid() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0,1,2,3, by AccountID =1.
+		for _ in 0..4 {
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+		}
+		assert_ok!(KittyArena::create_tournament(Origin::signed(2), 1_000, 10));
+		assert_ok!(KittyArena::create_tournament(Origin::signed(2), 1_000, 10));
+		assert_noop!(KittyArena::enter(Origin::signed(2), 0, 0), Error::<Test>::NotOwner);
+		assert_noop!(KittyArena::enter(Origin::signed(1), 2, 0), Error::<Test>::InvalidTournament);
+		// A kitty can be in one tournament at a time.
+		assert_ok!(KittyArena::enter(Origin::signed(1), 0, 0));
+		assert_noop!(
+			KittyArena::enter(Origin::signed(1), 1, 0),
+			pallet_kitties::Error::<Test>::AlreadyLocked
+		);
+		// MaxEntrants is 3 in the mock.
+		assert_ok!(KittyArena::enter(Origin::signed(1), 0, 1));
+		assert_ok!(KittyArena::enter(Origin::signed(1), 0, 2));
+		assert_noop!(KittyArena::enter(Origin::signed(1), 0, 3), Error::<Test>::TournamentFull);
+		// The tournament has started.
+		System::set_block_number(10);
+		assert_noop!(KittyArena::enter(Origin::signed(1), 1, 3), Error::<Test>::TournamentStarted);
+	});
+}
+
+#[test]
+fn tournament_is_resolved_at_start() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, and index=1, by AccountID =2.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::create(Origin::signed(2)));
+		assert_ok!(KittyArena::create_tournament(Origin::signed(3), 1_000, 10));
+		assert_ok!(KittyArena::enter(Origin::signed(1), 0, 0));
+		assert_ok!(KittyArena::enter(Origin::signed(2), 0, 1));
+		let (free_1, free_2) = (Balances::free_balance(1), Balances::free_balance(2));
+		assert_eq!(Balances::reserved_balance(3), 1_000);
+
+		run_to_block(10);
+		assert_eq!(Tournaments::<Test>::get(0), None);
+		// The winner gets the whole pot.
+		assert_eq!(Balances::free_balance(KittyArena::pot_account(0)), 0);
+		let prizes = (Balances::free_balance(1) - free_1, Balances::free_balance(2) - free_2);
+		assert!(prizes == (2_000, 0) || prizes == (0, 2_000));
+		assert_has_event!(Event::<Test>::TournamentResolved(0, 1, 2_000));
+		// The kitties are unlocked and the deposit is returned.
+		assert!(!<KittiesModule as KittyLock<u32>>::is_locked(0));
+		assert!(!<KittiesModule as KittyLock<u32>>::is_locked(1));
+		assert_eq!(Balances::reserved_balance(3), 0);
+	});
+}
+
+#[test]
+fn unpaid_prize_is_carried_over() {
+	new_test_ext().execute_with(|| {
+		// AccountID =4 doesn't exist, so it can't take a prize below the existential deposit.
+		// The first 2 entrants fight and the last one gets a bye.
+		let prepare = |entrants: Vec<(u64, u32)>| {
+			assert_ok!(KittyArena::create_tournament(Origin::signed(3), 0, 10));
+			let tournament_id = NextTournamentId::<Test>::get() - 1;
+			Tournaments::<Test>::mutate(tournament_id, |t| {
+				let t = t.as_mut().unwrap();
+				for entrant in entrants {
+					assert_ok!(t.entrants.try_push(entrant));
+				}
+			});
+			Balances::make_free_balance_be(&KittyArena::pot_account(tournament_id), 700);
+		};
+		// AccountID =4 wins the first match, and its prize goes to the next winner.
+		prepare(vec![(4, 0), (4, 1), (1, 2)]);
+		// AccountID =4 gets the bye, and its prize goes to the creator.
+		prepare(vec![(1, 3), (2, 4), (4, 5)]);
+		let (free_1, free_2, free_3) = (Balances::free_balance(1), Balances::free_balance(2), Balances::free_balance(3));
+
+		run_to_block(10);
+		assert_has_event!(Event::<Test>::PrizeUnpaid(0, 4, 350));
+		assert_has_event!(Event::<Test>::PrizeUnpaid(1, 4, 350));
+		assert_eq!(Balances::free_balance(4), 0);
+		assert_eq!(Balances::free_balance(KittyArena::pot_account(0)), 0);
+		assert_eq!(Balances::free_balance(KittyArena::pot_account(1)), 0);
+		// AccountID =1 gets 700 from the first, and AccountID =1 or 2 gets 350 from the second.
+		let prizes = (Balances::free_balance(1) - free_1, Balances::free_balance(2) - free_2);
+		assert!(prizes == (1_050, 0) || prizes == (700, 350));
+		// The creator gets 350 and both deposits back.
+		assert_eq!(Balances::free_balance(3) - free_3, 350 + 2_000);
+	});
+}
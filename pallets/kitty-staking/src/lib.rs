@@ -0,0 +1,185 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{
+        dispatch::DispatchResult,
+        pallet_prelude::*,
+        traits::{Currency, ExistenceRequirement, LockIdentifier},
+        PalletId,
+    };
+	use frame_system::pallet_prelude::*;
+    use codec::{Encode, Decode};
+    use sp_runtime::traits::{AccountIdConversion, SaturatedConversion, Saturating};
+    use pallet_kitties::traits::{KittyInspect, KittyLock};
+
+    /// The lock held on staked kitties in pallet_kitties, so they can't be traded.
+    pub const STAKING_LOCK_ID: LockIdentifier = *b"kittystk";
+
+    type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+    /// A staked kitty, earning rewards since the block of the last claim.
+    #[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
+    pub struct StakeInfo<AccountId, BlockNumber> {
+        pub staker: AccountId,
+        pub last_claim: BlockNumber,
+        pub weight: u32,
+    }
+
+    type StakeInfoOf<T> = StakeInfo<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::BlockNumber>;
+
+    #[pallet::config]
+	pub trait Config: frame_system::Config + pallet_kitties::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+        // The currency of the rewards, paid from the reward pot account.
+        type Currency: Currency<Self::AccountId>;
+        // The kitties pallet, to check the owners and lock the staked kitties.
+        type Kitties: KittyInspect<Self::AccountId, Self::KittyIndex> + KittyLock<Self::KittyIndex>;
+        // The reward for each block of a kitty with the weight of 1.
+        #[pallet::constant]
+        type RewardPerBlock: Get<BalanceOf<Self>>;
+        // The id of the reward pot account.
+        #[pallet::constant]
+        type PalletId: Get<PalletId>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+    #[pallet::event]
+	#[pallet::metadata(T::AccountId = "AccountId")]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+        /// A kitty was staked with its reward weight. [staker, kitty_id, weight]
+        KittyStaked(T::AccountId, T::KittyIndex, u32),
+        /// A kitty was unstaked. [staker, kitty_id]
+        KittyUnstaked(T::AccountId, T::KittyIndex),
+        /// Rewards were paid from the reward pot. [staker, kitty_id, amount]
+        RewardPaid(T::AccountId, T::KittyIndex, BalanceOf<T>),
+	}
+
+    /// Storage for the staked kitties.
+    #[pallet::storage]
+	#[pallet::getter(fn stakes)]
+	pub type Stakes<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, StakeInfoOf<T>>;
+
+	#[pallet::error]
+	pub enum Error<T> {
+        NotOwner,
+        AlreadyStaked,
+        NotStaked,
+        KittyOnLoan,
+	}
+
+	#[pallet::call]
+	impl<T:Config> Pallet<T> {
+
+        /// Stake a kitty. It's locked in pallet_kitties until unstaked.
+        #[pallet::weight(1_000)]
+        pub fn stake(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!Stakes::<T>::contains_key(kitty_id), Error::<T>::AlreadyStaked);
+            // Ensure only the kitty owner can stake it.
+            ensure!(Some(who.clone()) == T::Kitties::owner_of(kitty_id), Error::<T>::NotOwner);
+            let dna = T::Kitties::dna_of(kitty_id).ok_or(Error::<T>::NotOwner)?;
+            // The borrower uses the kitty until the loan expires, so the owner can't stake it meanwhile.
+            ensure!(!T::Kitties::is_lent(kitty_id), Error::<T>::KittyOnLoan);
+
+            T::Kitties::lock(kitty_id, STAKING_LOCK_ID)?;
+
+            let weight = Self::reward_weight(&dna);
+            Stakes::<T>::insert(kitty_id, StakeInfo {
+                staker: who.clone(),
+                last_claim: frame_system::Pallet::<T>::block_number(),
+                weight,
+            });
+
+            Self::deposit_event(Event::KittyStaked(who, kitty_id, weight));
+
+            Ok(())
+        }
+
+        /// Claim the rewards of a staked kitty, by its owner.
+        #[pallet::weight(1_000)]
+        pub fn claim(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let mut info = Self::stake_of_owner(&who, kitty_id)?;
+
+            Self::payout(kitty_id, &mut info);
+            Stakes::<T>::insert(kitty_id, info);
+
+            Ok(())
+        }
+
+        /// Claim the rewards and unstake a kitty by its owner, so it can be traded again.
+        #[pallet::weight(1_000)]
+        pub fn unstake(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let mut info = Self::stake_of_owner(&who, kitty_id)?;
+
+            T::Kitties::unlock(kitty_id, STAKING_LOCK_ID)?;
+
+            Self::payout(kitty_id, &mut info);
+            Stakes::<T>::remove(kitty_id);
+
+            Self::deposit_event(Event::KittyUnstaked(who, kitty_id));
+
+            Ok(())
+        }
+    }
+
+    // Helper functions.
+    impl<T: Config> Pallet<T> {
+        /// The account of the reward pot. It should be funded to pay the rewards.
+        pub fn account_id() -> T::AccountId {
+            T::PalletId::get().into_account()
+        }
+
+        /// The reward weight of a kitty by the rarity of its DNA.
+        /// Each gene byte of 0xf0 or above is rare (1 in 16), and adds 1 to the base weight of 1.
+        pub fn reward_weight(dna: &[u8; 16]) -> u32 {
+            1 + dna.iter().filter(|gene| **gene >= 0xf0).count() as u32
+        }
+
+        /// The rewards of a staked kitty since its last claim.
+        pub fn pending_rewards(info: &StakeInfoOf<T>) -> BalanceOf<T> {
+            let now = frame_system::Pallet::<T>::block_number();
+            let blocks = now.saturating_sub(info.last_claim).saturated_into::<u32>();
+            T::RewardPerBlock::get()
+                .saturating_mul(blocks.into())
+                .saturating_mul(info.weight.into())
+        }
+
+        // The stake of a kitty owned by `who`. The staker is the current owner, should the kitty
+        // have changed hands while staked, so the rewards never go to an ex-owner.
+        fn stake_of_owner(who: &T::AccountId, kitty_id: T::KittyIndex) -> Result<StakeInfoOf<T>, DispatchError> {
+            let mut info = Stakes::<T>::get(kitty_id).ok_or(Error::<T>::NotStaked)?;
+            ensure!(Some(who) == T::Kitties::owner_of(kitty_id).as_ref(), Error::<T>::NotOwner);
+            info.staker = who.clone();
+            Ok(info)
+        }
+
+        // Pay the pending rewards from the pot, as much as the pot can pay without being reaped.
+        // The unpaid rewards are forfeited, so unstaking never fails for an empty pot.
+        fn payout(kitty_id: T::KittyIndex, info: &mut StakeInfoOf<T>) {
+            let pot = Self::account_id();
+            let available = <T as Config>::Currency::free_balance(&pot)
+                .saturating_sub(<T as Config>::Currency::minimum_balance());
+            let amount = Self::pending_rewards(info).min(available);
+
+            info.last_claim = frame_system::Pallet::<T>::block_number();
+            if <T as Config>::Currency::transfer(&pot, &info.staker, amount, ExistenceRequirement::KeepAlive).is_ok() {
+                Self::deposit_event(Event::RewardPaid(info.staker.clone(), kitty_id, amount));
+            }
+        }
+    }
+}
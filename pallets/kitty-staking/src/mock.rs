@@ -0,0 +1,153 @@
+use crate as pallet_kitty_staking;
+use sp_core::H256;
+use frame_support::{parameter_types, PalletId};
+use sp_runtime::{
+	traits::{BlakeTwo256, IdentityLookup}, testing::{Header, TestSignature, UintAuthorityId},
+};
+use frame_system as system;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+type Balance = u128;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		KittiesModule: pallet_kitties::{Pallet, Call, Storage, Event<T>},
+		KittyStaking: pallet_kitty_staking::{Pallet, Call, Storage, Event<T>},
+        Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+        RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Storage},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+	type BaseCallFilter = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u128 = 500;
+	pub const MaxLocks: u32 = 50;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = MaxLocks;
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = Balance;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = pallet_balances::weights::SubstrateWeight<Test>;
+}
+
+impl pallet_randomness_collective_flip::Config for Test {}
+
+parameter_types! {
+    pub const StakeForEachKitty: u128 = 10_000;
+    pub const DepositPerByte: u128 = 100;
+    pub const MaxNameLength: u32 = 16;
+    pub const MaxMetadataLength: u32 = 64;
+    pub const MaxLoansPerBlock: u32 = 2;
+    pub const Treasury: u64 = 100;
+    pub const MaxGen0Supply: u32 = 100;
+    pub const MaxSalesPerKitty: u32 = 4;
+    pub const MaxRecentSales: u32 = 16;
+    pub const MaxMintsPerEra: u32 = 100;
+    pub const MintEraLength: u64 = 10;
+    pub const MaxSupply: u32 = 1_000;
+}
+
+impl pallet_kitties::Config for Test {
+	type Event = Event;
+    type Randomness = RandomnessCollectiveFlip;
+	type KittyIndex = u32;
+	type StakeForEachKitty = StakeForEachKitty;
+	type Currency = Balances;
+	type DepositPerByte = DepositPerByte;
+	type MaxNameLength = MaxNameLength;
+	type MaxMetadataLength = MaxMetadataLength;
+	type MaxLoansPerBlock = MaxLoansPerBlock;
+	type AdminOrigin = frame_system::EnsureRoot<u64>;
+	type Treasury = Treasury;
+	type MaxGen0Supply = MaxGen0Supply;
+	type MaxSupply = MaxSupply;
+	type MaxSalesPerKitty = MaxSalesPerKitty;
+	type MaxRecentSales = MaxRecentSales;
+	type OffchainSignature = TestSignature;
+	type SigningPublicKey = UintAuthorityId;
+	type MaxMintsPerEra = MaxMintsPerEra;
+	type MintEraLength = MintEraLength;
+}
+
+parameter_types! {
+    pub const RewardPerBlock: u128 = 10;
+    pub const StakingPalletId: PalletId = PalletId(*b"py/ktstk");
+}
+
+impl pallet_kitty_staking::Config for Test {
+	type Event = Event;
+	type Currency = Balances;
+	type Kitties = KittiesModule;
+	type RewardPerBlock = RewardPerBlock;
+	type PalletId = StakingPalletId;
+}
+
+// Helper macro.
+// Wrap System::assert_has_event() to macro assert_has_event!
+// Usage example: assert_has_event!(Event::<Test>::KittyStaked(1, 0, 1))
+#[macro_export]
+macro_rules! assert_has_event {
+	($x:expr) => {
+		System::assert_has_event(TestEvent::KittyStaking($x))
+	}
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+
+	// Genesis funds
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 10_000_000_000), (2, 10_000_000_000), (3, 9_000)],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,155 @@
+use crate::mock::{Event as TestEvent, new_test_ext, Balances, KittiesModule, KittyStaking, Origin, System, Test};
+use frame_support::{assert_ok, assert_noop, traits::Currency};
+use super::*;
+
+// Prepare kitty index=0, by AccountID =1, and fund the reward pot.
+fn create_kitty_and_fund_pot() {
+	assert_ok!(KittiesModule::create(Origin::signed(1)));
+	Balances::make_free_balance_be(&KittyStaking::account_id(), 1_000_000);
+}
+
+fn weight_of(kitty_id: u32) -> u32 {
+	KittyStaking::reward_weight(&KittiesModule::kitties(kitty_id).unwrap().0)
+}
+
+#[test]
+fn reward_weight_works() {
+	assert_eq!(KittyStaking::reward_weight(&[0u8; 16]), 1);
+	assert_eq!(KittyStaking::reward_weight(&[0xffu8; 16]), 17);
+	let mut dna = [0u8; 16];
+	dna[3] = 0xf0;
+	dna[7] = 0xef;
+	assert_eq!(KittyStaking::reward_weight(&dna), 2);
+}
+
+#[test]
+fn stake_works() {
+	new_test_ext().execute_with(|| {
+		create_kitty_and_fund_pot();
+		assert_ok!(KittyStaking::stake(Origin::signed(1), 0));
+		let weight = weight_of(0);
+		assert_eq!(Stakes::<Test>::get(0), Some(StakeInfo { staker: 1, last_claim: 1, weight }));
+		// Test the Event emitted already.
+		// KittyStaked(Staker, KittyIndex, Weight)
+		assert_has_event!(Event::<Test>::KittyStaked(1, 0, weight));
+		// The staked kitty can't be traded.
+		assert_noop!(
+			KittiesModule::transfer(Origin::signed(1), 2, 0),
+			pallet_kitties::Error::<Test>::KittyLocked
+		);
+	});
+}
+
+#[test]
+fn stake_failed_when_not_owner() {
+	new_test_ext().execute_with(|| {
+		create_kitty_and_fund_pot();
+		assert_noop!(KittyStaking::stake(Origin::signed(2), 0), Error::<Test>::NotOwner);
+		// No KittyID =1.
+		assert_noop!(KittyStaking::stake(Origin::signed(1), 1), Error::<Test>::NotOwner);
+	});
+}
+
+#[test]
+fn stake_failed_when_already_staked() {
+	new_test_ext().execute_with(|| {
+		create_kitty_and_fund_pot();
+		assert_ok!(KittyStaking::stake(Origin::signed(1), 0));
+		assert_noop!(KittyStaking::stake(Origin::signed(1), 0), Error::<Test>::AlreadyStaked);
+	});
+}
+
+#[test]
+fn claim_works() {
+	new_test_ext().execute_with(|| {
+		create_kitty_and_fund_pot();
+		assert_ok!(KittyStaking::stake(Origin::signed(1), 0));
+		let free = Balances::free_balance(1);
+		// 10 blocks later, the reward is 10 for each block and weight.
+		System::set_block_number(11);
+		assert_ok!(KittyStaking::claim(Origin::signed(1), 0));
+		let reward = 10 * 10 * weight_of(0) as u128;
+		assert_eq!(Balances::free_balance(1), free + reward);
+		assert_eq!(Stakes::<Test>::get(0).unwrap().last_claim, 11);
+		assert_has_event!(Event::<Test>::RewardPaid(1, 0, reward));
+	});
+}
+
+#[test]
+fn claim_pays_what_the_pot_can_pay() {
+	new_test_ext().execute_with(|| {
+		create_kitty_and_fund_pot();
+		assert_ok!(KittyStaking::stake(Origin::signed(1), 0));
+		// The pot only has 100 above the existential deposit.
+		Balances::make_free_balance_be(&KittyStaking::account_id(), 600);
+		let free = Balances::free_balance(1);
+		System::set_block_number(11);
+		assert_ok!(KittyStaking::claim(Origin::signed(1), 0));
+		assert_eq!(Balances::free_balance(1), free + 100);
+		assert_eq!(Balances::free_balance(KittyStaking::account_id()), 500);
+	});
+}
+
+#[test]
+fn claim_failed_when_not_owner() {
+	new_test_ext().execute_with(|| {
+		create_kitty_and_fund_pot();
+		assert_noop!(KittyStaking::claim(Origin::signed(1), 0), Error::<Test>::NotStaked);
+		assert_ok!(KittyStaking::stake(Origin::signed(1), 0));
+		assert_noop!(KittyStaking::claim(Origin::signed(2), 0), Error::<Test>::NotOwner);
+	});
+}
+
+#[test]
+fn unstake_works() {
+	new_test_ext().execute_with(|| {
+		create_kitty_and_fund_pot();
+		assert_ok!(KittyStaking::stake(Origin::signed(1), 0));
+		let free = Balances::free_balance(1);
+		System::set_block_number(6);
+		assert_ok!(KittyStaking::unstake(Origin::signed(1), 0));
+		// The pending rewards are paid.
+		assert_eq!(Balances::free_balance(1), free + 5 * 10 * weight_of(0) as u128);
+		assert_eq!(Stakes::<Test>::get(0), None);
+		assert_has_event!(Event::<Test>::KittyUnstaked(1, 0));
+		// The kitty can be traded again.
+		assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+	});
+}
+
+#[test]
+fn unstake_failed_when_not_owner() {
+	new_test_ext().execute_with(|| {
+		create_kitty_and_fund_pot();
+		assert_ok!(KittyStaking::stake(Origin::signed(1), 0));
+		assert_noop!(KittyStaking::unstake(Origin::signed(2), 0), Error::<Test>::NotOwner);
+	});
+}
+
+#[test]
+fn claim_pays_the_current_owner() {
+	new_test_ext().execute_with(|| {
+		create_kitty_and_fund_pot();
+		assert_ok!(KittyStaking::stake(Origin::signed(1), 0));
+		// The kitty changed hands while staked, e.g. in a migration.
+		pallet_kitties::Owner::<Test>::insert(0, 2);
+		System::set_block_number(11);
+		assert_noop!(KittyStaking::claim(Origin::signed(1), 0), Error::<Test>::NotOwner);
+
+		let free = Balances::free_balance(2);
+		assert_ok!(KittyStaking::unstake(Origin::signed(2), 0));
+		let reward = 10 * 10 * weight_of(0) as u128;
+		assert_eq!(Balances::free_balance(2), free + reward);
+		assert_has_event!(Event::<Test>::RewardPaid(2, 0, reward));
+	});
+}
+
+#[test]
+fn stake_failed_when_lent() {
+	new_test_ext().execute_with(|| {
+		create_kitty_and_fund_pot();
+		assert_ok!(KittiesModule::lend(Origin::signed(1), 0, 2, 10, 0));
+		assert_ok!(KittiesModule::accept_loan(Origin::signed(2), 0));
+		assert_noop!(KittyStaking::stake(Origin::signed(1), 0), Error::<Test>::KittyOnLoan);
+	});
+}
@@ -0,0 +1,160 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{
+        dispatch::DispatchResult,
+        pallet_prelude::*,
+        traits::{LockIdentifier, tokens::fungibles::{Create, Inspect, Mutate}},
+        transactional,
+        PalletId,
+    };
+	use frame_system::pallet_prelude::*;
+    use codec::{Encode, Decode};
+    use sp_runtime::traits::{AccountIdConversion, Convert, One, Zero};
+    use pallet_kitties::traits::{KittyInspect, KittyLock, KittyTransfer};
+
+    /// The lock held on the kitties in a vault in pallet_kitties.
+    pub const VAULT_LOCK_ID: LockIdentifier = *b"kittyvlt";
+
+    type AssetIdOf<T> = <<T as Config>::Assets as Inspect<<T as frame_system::Config>::AccountId>>::AssetId;
+    type AssetBalanceOf<T> = <<T as Config>::Assets as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
+
+    /// A kitty locked in the vault, with the fungible shares minted for it.
+    #[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
+    pub struct Vault<AccountId, AssetId, Balance> {
+        /// The account which fractionalized the kitty. The owner keeps the ownership until the kitty is redeemed.
+        pub owner: AccountId,
+        pub asset_id: AssetId,
+        pub shares: Balance,
+    }
+
+    type VaultOf<T> = Vault<<T as frame_system::Config>::AccountId, AssetIdOf<T>, AssetBalanceOf<T>>;
+
+    #[pallet::config]
+	pub trait Config: frame_system::Config + pallet_kitties::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+        // The kitties pallet, to lock the kitties in the vault and hand them to the redeemers.
+        type Kitties: KittyInspect<Self::AccountId, Self::KittyIndex>
+            + KittyLock<Self::KittyIndex>
+            + KittyTransfer<Self::AccountId, Self::KittyIndex>;
+        // The fungible assets for the shares, e.g. pallet_assets.
+        type Assets: Create<Self::AccountId> + Mutate<Self::AccountId>;
+        // The asset id of the shares of a kitty, None if the kitty has no share asset id.
+        // The ids must be reserved for the vault, e.g. with the call filter, or anyone could
+        // create an asset first and block the kitty. Distinct kitties must have distinct ids.
+        type ShareAssetId: Convert<Self::KittyIndex, Option<AssetIdOf<Self>>>;
+        // The id of the vault account, the admin of the share assets.
+        #[pallet::constant]
+        type PalletId: Get<PalletId>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+    #[pallet::event]
+	#[pallet::metadata(T::AccountId = "AccountId")]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+        /// A kitty was locked into the vault and its shares were minted. [owner, kitty_id, asset_id, shares]
+        KittyFractionalized(T::AccountId, T::KittyIndex, AssetIdOf<T>, AssetBalanceOf<T>),
+        /// A kitty was redeemed with all its shares. [redeemer, kitty_id]
+        KittyRedeemed(T::AccountId, T::KittyIndex),
+	}
+
+    /// Storage for the kitties in the vault.
+    #[pallet::storage]
+	#[pallet::getter(fn vaults)]
+	pub type Vaults<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, VaultOf<T>>;
+
+    /// Storage for the share assets which were created, to reuse them when a kitty is
+    /// fractionalized again.
+    #[pallet::storage]
+	pub type ShareAssets<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, AssetIdOf<T>>;
+
+	#[pallet::error]
+	pub enum Error<T> {
+        NotOwner,
+        ZeroShares,
+        AlreadyInVault,
+        NotInVault,
+        NotAllShares,
+        NoShareAssetId,
+	}
+
+	#[pallet::call]
+	impl<T:Config> Pallet<T> {
+
+        /// Lock a kitty into the vault and mint `shares` of its share asset to the owner.
+        #[pallet::weight(1_000)]
+        #[transactional]
+        pub fn fractionalize(origin: OriginFor<T>, kitty_id: T::KittyIndex, shares: AssetBalanceOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            // Ensure only the kitty owner can fractionalize it.
+            ensure!(Some(who.clone()) == T::Kitties::owner_of(kitty_id), Error::<T>::NotOwner);
+            ensure!(!Vaults::<T>::contains_key(kitty_id), Error::<T>::AlreadyInVault);
+            ensure!(!shares.is_zero(), Error::<T>::ZeroShares);
+
+            let asset_id = match ShareAssets::<T>::get(kitty_id) {
+                Some(asset_id) => asset_id,
+                None => {
+                    let asset_id = T::ShareAssetId::convert(kitty_id).ok_or(Error::<T>::NoShareAssetId)?;
+                    // The shares are not sufficient, so holding them doesn't keep an account alive.
+                    T::Assets::create(asset_id, Self::account_id(), false, One::one())?;
+                    ShareAssets::<T>::insert(kitty_id, asset_id);
+                    asset_id
+                },
+            };
+
+            // The kitty is held by the vault only, so it can always be handed to the redeemer.
+            T::Kitties::lock_exclusive(kitty_id, VAULT_LOCK_ID)?;
+            T::Assets::mint_into(asset_id, &who, shares)?;
+
+            Vaults::<T>::insert(kitty_id, Vault { owner: who.clone(), asset_id, shares });
+
+            Self::deposit_event(Event::KittyFractionalized(who, kitty_id, asset_id, shares));
+
+            Ok(())
+        }
+
+        /// Redeem a kitty from the vault by burning all its shares. The redeemer becomes the owner.
+        #[pallet::weight(1_000)]
+        #[transactional]
+        pub fn redeem(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let vault = Vaults::<T>::get(kitty_id).ok_or(Error::<T>::NotInVault)?;
+            ensure!(T::Assets::balance(vault.asset_id, &who) >= vault.shares, Error::<T>::NotAllShares);
+
+            // The kitty is handed over by its current owner, not necessarily the one who fractionalized it.
+            let owner = T::Kitties::owner_of(kitty_id).ok_or(Error::<T>::NotInVault)?;
+
+            T::Assets::burn_from(vault.asset_id, &who, vault.shares)?;
+            T::Kitties::unlock(kitty_id, VAULT_LOCK_ID)?;
+            if who != owner {
+                T::Kitties::transfer_kitty(&owner, &who, kitty_id)?;
+            }
+            Vaults::<T>::remove(kitty_id);
+
+            Self::deposit_event(Event::KittyRedeemed(who, kitty_id));
+
+            Ok(())
+        }
+    }
+
+    // Helper functions.
+    impl<T: Config> Pallet<T> {
+        /// The vault account, the admin of the share assets.
+        pub fn account_id() -> T::AccountId {
+            T::PalletId::get().into_account()
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use crate::mock::{Event as TestEvent, new_test_ext, Assets, Balances, KittiesModule, KittyVault, Origin, System, Test};
+use frame_support::{assert_ok, assert_noop};
+use pallet_kitties::traits::KittyLock;
+use super::*;
+
+#[test]
+fn fractionalize_works() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittyVault::fractionalize(Origin::signed(1), 0, 100));
+		assert_eq!(Vaults::<Test>::get(0), Some(Vault { owner: 1, asset_id: 0, shares: 100 }));
+		assert_eq!(Assets::balance(0, 1), 100);
+		// The kitty in the vault can't be traded.
+		assert!(<KittiesModule as KittyLock<u32>>::is_locked(0));
+		assert_noop!(
+			KittiesModule::transfer(Origin::signed(1), 2, 0),
+			pallet_kitties::Error::<Test>::KittyLocked
+		);
+		// Test the Event emitted already.
+		// KittyFractionalized(Owner, KittyIndex, AssetId, Shares)
+		assert_has_event!(Event::<Test>::KittyFractionalized(1, 0, 0, 100));
+	});
+}
+
+#[test]
+fn fractionalize_failed_when_invalid() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_noop!(KittyVault::fractionalize(Origin::signed(2), 0, 100), Error::<Test>::NotOwner);
+		assert_noop!(KittyVault::fractionalize(Origin::signed(1), 0, 0), Error::<Test>::ZeroShares);
+		assert_ok!(KittyVault::fractionalize(Origin::signed(1), 0, 100));
+		assert_noop!(KittyVault::fractionalize(Origin::signed(1), 0, 100), Error::<Test>::AlreadyInVault);
+	});
+}
+
+#[test]
+fn fractionalize_failed_when_no_share_asset_id() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=2, by AccountID =1, which has no share asset id in the mock.
+		for _ in 0..3 {
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+		}
+		assert_noop!(KittyVault::fractionalize(Origin::signed(1), 2, 100), Error::<Test>::NoShareAssetId);
+		assert!(!<KittiesModule as KittyLock<u32>>::is_locked(2));
+	});
+}
+
+#[test]
+fn redeem_works() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, and give all the shares to AccountID=2.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittyVault::fractionalize(Origin::signed(1), 0, 100));
+		assert_ok!(Assets::transfer(Origin::signed(1), 0, 2, 100));
+		assert_ok!(KittyVault::redeem(Origin::signed(2), 0));
+		// AccountID=2 owns the kitty with the stake, and the shares are burned.
+		assert_eq!(KittiesModule::owner(0), Some(2));
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(2), 10_000);
+		assert_eq!(Assets::balance(0, 2), 0);
+		assert_eq!(Vaults::<Test>::get(0), None);
+		assert!(!<KittiesModule as KittyLock<u32>>::is_locked(0));
+		assert_has_event!(Event::<Test>::KittyRedeemed(2, 0));
+	});
+}
+
+#[test]
+fn redeem_works_after_force_transfer_refused() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, in the vault.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittyVault::fractionalize(Origin::signed(1), 0, 100));
+		// The admin can't move the kitty out of the vault.
+		assert_noop!(
+			KittiesModule::force_transfer(Origin::root(), 3, 0),
+			pallet_kitties::Error::<Test>::KittyLocked
+		);
+		// So the shares still redeem it from its owner.
+		assert_ok!(Assets::transfer(Origin::signed(1), 0, 2, 100));
+		assert_ok!(KittyVault::redeem(Origin::signed(2), 0));
+		assert_eq!(KittiesModule::owner(0), Some(2));
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(2), 10_000);
+	});
+}
+
+#[test]
+fn kitty_in_vault_can_not_be_locked_by_other_pallets() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, in the vault.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittyVault::fractionalize(Origin::signed(1), 0, 100));
+		// The owner can't stake it, as pallet-kitty-staking locks the staked kitties.
+		assert_noop!(
+			<KittiesModule as KittyLock<u32>>::lock(0, *b"kittystk"),
+			pallet_kitties::Error::<Test>::KittyLocked
+		);
+		// So the kitty can always be redeemed.
+		assert_ok!(Assets::transfer(Origin::signed(1), 0, 2, 100));
+		assert_ok!(KittyVault::redeem(Origin::signed(2), 0));
+		assert_eq!(KittiesModule::owner(0), Some(2));
+	});
+}
+
+#[test]
+fn fractionalize_failed_when_locked() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, staked.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(<KittiesModule as KittyLock<u32>>::lock(0, *b"kittystk"));
+		assert_noop!(
+			KittyVault::fractionalize(Origin::signed(1), 0, 100),
+			pallet_kitties::Error::<Test>::KittyLocked
+		);
+	});
+}
+
+#[test]
+fn redeem_failed_when_not_all_shares() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1, and give some shares to AccountID=2.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_noop!(KittyVault::redeem(Origin::signed(1), 0), Error::<Test>::NotInVault);
+		assert_ok!(KittyVault::fractionalize(Origin::signed(1), 0, 100));
+		assert_ok!(Assets::transfer(Origin::signed(1), 0, 2, 40));
+		assert_noop!(KittyVault::redeem(Origin::signed(1), 0), Error::<Test>::NotAllShares);
+		assert_noop!(KittyVault::redeem(Origin::signed(2), 0), Error::<Test>::NotAllShares);
+	});
+}
+
+#[test]
+fn kitty_can_be_fractionalized_again() {
+	new_test_ext().execute_with(|| {
+		// Prepare kitty index=0, by AccountID =1.
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittyVault::fractionalize(Origin::signed(1), 0, 100));
+		assert_ok!(KittyVault::redeem(Origin::signed(1), 0));
+		assert_eq!(KittiesModule::owner(0), Some(1));
+		// The share asset is reused.
+		assert_ok!(KittyVault::fractionalize(Origin::signed(1), 0, 10));
+		assert_eq!(Assets::balance(0, 1), 10);
+	});
+}
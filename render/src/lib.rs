@@ -0,0 +1,180 @@
+//! Kitty images.
+//!
+//! Draw a kitty as an SVG avatar from its 16-byte DNA, with the gene layout of
+//! `pallet_kitties::genes`. The image depends on the DNA only, so every client draws a given
+//! kitty the same way.
+
+use std::fmt::Write;
+use pallet_kitties::genes::{EarShape, EyeShape, Genes, MouthShape, Pattern};
+
+/// The width and height of the images.
+pub const SIZE: u32 = 200;
+
+/// The SVG image of the kitty with `dna`. Its ids are derived from the DNA, so the images of
+/// different kitties can be inlined in the same page.
+pub fn render_svg(dna: &[u8; 16]) -> String {
+	let genes = Genes::from_dna(dna);
+	let body = rgb(genes.body_color);
+	let pattern = rgb(genes.pattern_color);
+
+	let mut svg = format!(
+		r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 200 200">"#,
+		size = SIZE,
+	);
+	// Writing to a String can't fail.
+	let _ = write!(
+		svg,
+		r#"<rect width="200" height="200" fill="hsl({}, 60%, 90%)"/>"#,
+		genes.background_hue as u32 * 360 / 256,
+	);
+	svg.push_str(&ears(genes.ear_shape, &body, &pattern));
+	let head = format!("head-{}", hex::encode(dna));
+	let _ = write!(svg, r#"<clipPath id="{}"><circle cx="100" cy="110" r="60"/></clipPath>"#, head);
+	let _ = write!(svg, r#"<circle cx="100" cy="110" r="60" fill="{}"/>"#, body);
+	let _ = write!(
+		svg,
+		r#"<g clip-path="url(#{0})" fill="{1}" stroke="{1}">{2}</g>"#,
+		head, pattern, markings(genes.pattern),
+	);
+	svg.push_str(&eyes(genes.eye_shape, &rgb(genes.eye_color)));
+	svg.push_str(r##"<polygon points="94,120 106,120 100,128" fill="#f4a6b8"/>"##);
+	svg.push_str(mouth(genes.mouth_shape));
+	svg.push_str("</svg>");
+	svg
+}
+
+fn rgb(color: [u8; 3]) -> String {
+	format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+fn ears(shape: EarShape, body: &str, inner: &str) -> String {
+	match shape {
+		EarShape::Pointed => format!(
+			r#"<polygon points="48,90 58,30 95,62" fill="{0}"/><polygon points="152,90 142,30 105,62" fill="{0}"/><polygon points="60,78 64,45 84,63" fill="{1}"/><polygon points="140,78 136,45 116,63" fill="{1}"/>"#,
+			body, inner,
+		),
+		EarShape::Round => format!(
+			r#"<circle cx="60" cy="62" r="24" fill="{0}"/><circle cx="140" cy="62" r="24" fill="{0}"/><circle cx="60" cy="62" r="12" fill="{1}"/><circle cx="140" cy="62" r="12" fill="{1}"/>"#,
+			body, inner,
+		),
+		EarShape::Folded => format!(
+			r#"<path d="M50 80 Q55 45 90 58 Q70 62 62 84 Z" fill="{0}"/><path d="M150 80 Q145 45 110 58 Q130 62 138 84 Z" fill="{0}"/>"#,
+			body,
+		),
+	}
+}
+
+fn markings(pattern: Pattern) -> &'static str {
+	match pattern {
+		Pattern::Plain => "",
+		Pattern::Stripes => concat!(
+			r#"<path d="M70 50 Q80 75 70 95" stroke-width="8" fill="none"/>"#,
+			r#"<path d="M100 48 L100 92" stroke-width="8" fill="none"/>"#,
+			r#"<path d="M130 50 Q120 75 130 95" stroke-width="8" fill="none"/>"#,
+		),
+		Pattern::Spots => concat!(
+			r#"<circle cx="68" cy="80" r="9"/>"#,
+			r#"<circle cx="128" cy="70" r="7"/>"#,
+			r#"<circle cx="140" cy="145" r="10"/>"#,
+			r#"<circle cx="62" cy="150" r="6"/>"#,
+		),
+		Pattern::Patches => concat!(
+			r#"<ellipse cx="65" cy="95" rx="30" ry="40"/>"#,
+			r#"<ellipse cx="145" cy="160" rx="35" ry="25"/>"#,
+		),
+	}
+}
+
+fn eyes(shape: EyeShape, iris: &str) -> String {
+	let eye = |cx| match shape {
+		EyeShape::Round => format!(
+			r#"<circle cx="{0}" cy="100" r="11" fill="{1}"/><circle cx="{0}" cy="100" r="5" fill="black"/>"#,
+			cx, iris,
+		),
+		EyeShape::Almond => format!(
+			r#"<ellipse cx="{0}" cy="100" rx="14" ry="8" fill="{1}"/><ellipse cx="{0}" cy="100" rx="3" ry="7" fill="black"/>"#,
+			cx, iris,
+		),
+		EyeShape::Sleepy => format!(
+			r#"<path d="M{0} 100 a12 12 0 0 0 24 0 Z" fill="{1}"/><path d="M{0} 100 h24" stroke="black" stroke-width="3"/>"#,
+			cx - 12, iris,
+		),
+		EyeShape::Wide => format!(
+			r#"<circle cx="{0}" cy="100" r="15" fill="white"/><circle cx="{0}" cy="100" r="10" fill="{1}"/><circle cx="{0}" cy="100" r="5" fill="black"/>"#,
+			cx, iris,
+		),
+	};
+	format!("{}{}", eye(78), eye(122))
+}
+
+fn mouth(shape: MouthShape) -> &'static str {
+	match shape {
+		MouthShape::Smile =>
+			r#"<path d="M88 134 Q100 146 112 134" stroke="black" stroke-width="3" fill="none"/>"#,
+		MouthShape::Flat =>
+			r#"<path d="M90 138 H110" stroke="black" stroke-width="3"/>"#,
+		MouthShape::Open =>
+			r##"<ellipse cx="100" cy="140" rx="8" ry="6" fill="#7a2a3a"/>"##,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn dna_with(pattern: u8, eye_shape: u8, ear_shape: u8, mouth_shape: u8) -> [u8; 16] {
+		let mut dna = [0x5au8; 16];
+		dna[pallet_kitties::genes::PATTERN] = pattern;
+		dna[pallet_kitties::genes::EYE_SHAPE] = eye_shape;
+		dna[pallet_kitties::genes::EAR_SHAPE] = ear_shape;
+		dna[pallet_kitties::genes::MOUTH_SHAPE] = mouth_shape;
+		dna
+	}
+
+	#[test]
+	fn same_dna_renders_the_same_svg() {
+		let dna = [0x12u8, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 1, 2, 3, 4, 5, 6, 7, 8];
+		assert_eq!(render_svg(&dna), render_svg(&dna));
+		assert_ne!(render_svg(&dna), render_svg(&[0u8; 16]));
+	}
+
+	#[test]
+	fn every_gene_variant_renders() {
+		// 4 patterns and eye shapes, 3 ear and mouth shapes.
+		for pattern in 0..4 {
+			for eye_shape in 0..4 {
+				for ear_shape in 0..3 {
+					for mouth_shape in 0..3 {
+						let dna = dna_with(pattern, eye_shape, ear_shape, mouth_shape);
+						let genes = Genes::from_dna(&dna);
+						let svg = render_svg(&dna);
+						assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>"));
+						assert!(svg.contains(&ears(genes.ear_shape, &rgb(genes.body_color), &rgb(genes.pattern_color))));
+						assert!(svg.contains(markings(genes.pattern)));
+						assert!(svg.contains(&eyes(genes.eye_shape, &rgb(genes.eye_color))));
+						assert!(svg.contains(mouth(genes.mouth_shape)));
+					}
+				}
+			}
+		}
+		// The variants are all drawn differently.
+		let mut svgs: Vec<_> = (0..4).map(|p| render_svg(&dna_with(p, 0, 0, 0))).collect();
+		svgs.extend((1..4).map(|e| render_svg(&dna_with(0, e, 0, 0))));
+		svgs.extend((1..3).map(|e| render_svg(&dna_with(0, 0, e, 0))));
+		svgs.extend((1..3).map(|m| render_svg(&dna_with(0, 0, 0, m))));
+		let count = svgs.len();
+		svgs.sort();
+		svgs.dedup();
+		assert_eq!(svgs.len(), count);
+	}
+
+	#[test]
+	fn clip_path_id_depends_on_the_dna() {
+		let dna = dna_with(1, 0, 0, 0);
+		let svg = render_svg(&dna);
+		let id = format!("head-{}", hex::encode(dna));
+		assert!(svg.contains(&format!(r#"<clipPath id="{}">"#, id)));
+		assert!(svg.contains(&format!(r#"clip-path="url(#{})""#, id)));
+		assert!(!render_svg(&dna_with(2, 0, 0, 0)).contains(&id));
+	}
+}
@@ -0,0 +1,29 @@
+//! Draw a kitty as an SVG image from its DNA.
+
+use std::{fs, io::Write, path::PathBuf};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "kitties-render", about = "Draw a kitty as an SVG image from its DNA.")]
+struct Opt {
+	/// The 16-byte DNA in hex, e.g. from the `KittyCreated` event.
+	dna: String,
+
+	/// The output file. Defaults to stdout.
+	#[structopt(long, parse(from_os_str))]
+	output: Option<PathBuf>,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+	let opt = Opt::from_args();
+	let mut dna = [0u8; 16];
+	hex::decode_to_slice(opt.dna.trim_start_matches("0x"), &mut dna)
+		.map_err(|e| format!("Invalid DNA, expected 16 bytes in hex: {}", e))?;
+
+	let svg = kitties_render::render_svg(&dna);
+	match opt.output {
+		Some(path) => fs::write(path, svg)?,
+		None => std::io::stdout().write_all(svg.as_bytes())?,
+	}
+	Ok(())
+}
@@ -13,7 +13,8 @@ use sp_runtime::{
 	transaction_validity::{TransactionValidity, TransactionSource},
 };
 use sp_runtime::traits::{
-	BlakeTwo256, Block as BlockT, AccountIdLookup, Verify, IdentifyAccount, NumberFor,
+	BlakeTwo256, Block as BlockT, AccountIdLookup, Verify, IdentifyAccount, NumberFor, Convert,
+	AccountIdConversion,
 };
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -30,8 +31,8 @@ pub use pallet_timestamp::Call as TimestampCall;
 pub use pallet_balances::Call as BalancesCall;
 pub use sp_runtime::{Permill, Perbill};
 pub use frame_support::{
-	construct_runtime, parameter_types, StorageValue,
-	traits::{KeyOwnerProofSystem, Randomness},
+	construct_runtime, parameter_types, StorageValue, PalletId,
+	traits::{Filter, KeyOwnerProofSystem, Randomness},
 	weights::{
 		Weight, IdentityFee,
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
@@ -42,6 +43,9 @@ use pallet_transaction_payment::CurrencyAdapter;
 /// Import the template pallet.
 pub use pallet_template;
 pub use pallet_kitties;
+pub use pallet_kitty_staking;
+pub use pallet_kitty_arena;
+pub use pallet_kitty_vault;
 
 /// An index to a block.
 pub type BlockNumber = u32;
@@ -98,10 +102,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 102,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 };
 
 /// This determines the average expected block time that we are targeting.
@@ -143,11 +147,23 @@ parameter_types! {
 	pub const SS58Prefix: u8 = 42;
 }
 
+/// Reject the creation of assets in the ids of the kitty shares, which only the vault creates.
+pub struct BaseFilter;
+impl Filter<Call> for BaseFilter {
+	fn filter(call: &Call) -> bool {
+		match call {
+			Call::Assets(pallet_assets::Call::create(id, ..)) |
+			Call::Assets(pallet_assets::Call::force_create(id, ..)) => !KittyShareAssetId::is_share_asset(*id),
+			_ => true,
+		}
+	}
+}
+
 // Configure FRAME pallets to include in runtime.
 
 impl frame_system::Config for Runtime {
 	/// The basic call filter to use in dispatchable.
-	type BaseCallFilter = ();
+	type BaseCallFilter = BaseFilter;
 	/// Block & extrinsics weights: base values and limits.
 	type BlockWeights = BlockWeights;
 	/// The maximum length of a block (in bytes).
@@ -267,6 +283,30 @@ impl pallet_nicks::Config for Runtime {
     type Event = Event;
 }
 
+parameter_types! {
+	pub const AssetDeposit: Balance = 100;
+	pub const ApprovalDeposit: Balance = 1;
+	pub const AssetsStringLimit: u32 = 50;
+	pub const MetadataDepositBase: Balance = 10;
+	pub const MetadataDepositPerByte: Balance = 1;
+}
+
+impl pallet_assets::Config for Runtime {
+	type Event = Event;
+	type Balance = u128;
+	type AssetId = u32;
+	type Currency = Balances;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type AssetDeposit = AssetDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = AssetsStringLimit;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
+}
+
 parameter_types! {
 	pub const ExistentialDeposit: u128 = 500;
 	pub const MaxLocks: u32 = 50;
@@ -309,6 +349,18 @@ impl pallet_template::Config for Runtime {
 
 parameter_types! {
 	pub const StakeForEachKitty: u128 = 1_000;
+	pub const KittyDepositPerByte: u128 = 10;
+	pub const MaxKittyNameLength: u32 = 32;
+	pub const MaxKittyMetadataLength: u32 = 256;
+	pub const MaxKittyLoansPerBlock: u32 = 64;
+	pub const KittyTreasuryPalletId: PalletId = PalletId(*b"py/kttry");
+	pub KittyTreasury: AccountId = KittyTreasuryPalletId::get().into_account();
+	pub const MaxKittyGen0Supply: u32 = 10_000;
+	pub const MaxKittySupply: u32 = 1_000_000;
+	pub const MaxKittySalesPerKitty: u32 = 16;
+	pub const MaxRecentKittySales: u32 = 256;
+	pub const MaxKittyMintsPerEra: u32 = 20;
+	pub const KittyMintEraLength: BlockNumber = HOURS;
 }
 
 /// Configure the pallet-template in pallets/template.
@@ -318,6 +370,83 @@ impl pallet_kitties::Config for Runtime {
 	type KittyIndex = u32;
 	type StakeForEachKitty = StakeForEachKitty;
 	type Currency = Balances;
+	type DepositPerByte = KittyDepositPerByte;
+	type MaxNameLength = MaxKittyNameLength;
+	type MaxMetadataLength = MaxKittyMetadataLength;
+	type MaxLoansPerBlock = MaxKittyLoansPerBlock;
+	type AdminOrigin = frame_system::EnsureRoot<AccountId>;
+	type Treasury = KittyTreasury;
+	type MaxGen0Supply = MaxKittyGen0Supply;
+	type MaxSupply = MaxKittySupply;
+	type MaxSalesPerKitty = MaxKittySalesPerKitty;
+	type MaxRecentSales = MaxRecentKittySales;
+	type OffchainSignature = Signature;
+	type SigningPublicKey = <Signature as Verify>::Signer;
+	type MaxMintsPerEra = MaxKittyMintsPerEra;
+	type MintEraLength = KittyMintEraLength;
+}
+
+parameter_types! {
+	pub const KittyRewardPerBlock: u128 = 1;
+	pub const KittyStakingPalletId: PalletId = PalletId(*b"py/ktstk");
+}
+
+/// Configure the pallet-kitty-staking in pallets/kitty-staking.
+impl pallet_kitty_staking::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type Kitties = KittiesModule;
+	type RewardPerBlock = KittyRewardPerBlock;
+	type PalletId = KittyStakingPalletId;
+}
+
+parameter_types! {
+	pub const MaxArenaEntrants: u32 = 64;
+	pub const MaxTournamentsPerBlock: u32 = 16;
+	pub const TournamentDeposit: u128 = 10_000;
+	pub const KittyArenaPalletId: PalletId = PalletId(*b"py/ktarn");
+}
+
+/// Configure the pallet-kitty-arena in pallets/kitty-arena.
+impl pallet_kitty_arena::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type Kitties = KittiesModule;
+	type Randomness = RandomnessCollectiveFlip;
+	type MaxEntrants = MaxArenaEntrants;
+	type MaxTournamentsPerBlock = MaxTournamentsPerBlock;
+	type TournamentDeposit = TournamentDeposit;
+	type PalletId = KittyArenaPalletId;
+}
+
+/// The share assets of the kitties in the vault use the asset ids from `1 << 31`,
+/// the lower ids are left for the other assets. See `BaseFilter`.
+/// The kitties from index `1 << 31`, e.g. imported at genesis, have no share asset.
+pub struct KittyShareAssetId;
+impl KittyShareAssetId {
+	const FIRST_ID: u32 = 1 << 31;
+
+	pub fn is_share_asset(id: u32) -> bool {
+		id >= Self::FIRST_ID
+	}
+}
+impl Convert<u32, Option<u32>> for KittyShareAssetId {
+	fn convert(kitty_id: u32) -> Option<u32> {
+		if kitty_id < Self::FIRST_ID { Some(Self::FIRST_ID + kitty_id) } else { None }
+	}
+}
+
+parameter_types! {
+	pub const KittyVaultPalletId: PalletId = PalletId(*b"py/ktvlt");
+}
+
+/// Configure the pallet-kitty-vault in pallets/kitty-vault.
+impl pallet_kitty_vault::Config for Runtime {
+	type Event = Event;
+	type Kitties = KittiesModule;
+	type Assets = Assets;
+	type ShareAssetId = KittyShareAssetId;
+	type PalletId = KittyVaultPalletId;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -336,9 +465,13 @@ construct_runtime!(
 		TransactionPayment: pallet_transaction_payment::{Pallet, Storage},
 		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
 		Nicks: pallet_nicks::{Pallet, Call, Storage, Event<T>},
+		Assets: pallet_assets::{Pallet, Call, Storage, Event<T>},
 		// Include the custom logic from the pallet-template in the runtime.
 		TemplateModule: pallet_template::{Pallet, Call, Storage, Event<T>},
-		KittiesModule: pallet_kitties::{Pallet, Call, Storage, Event<T>},
+		KittiesModule: pallet_kitties::{Pallet, Call, Config, Storage, Event<T>, ValidateUnsigned},
+		KittyStaking: pallet_kitty_staking::{Pallet, Call, Storage, Event<T>},
+		KittyArena: pallet_kitty_arena::{Pallet, Call, Storage, Event<T>},
+		KittyVault: pallet_kitty_vault::{Pallet, Call, Storage, Event<T>},
 	}
 );
 
@@ -356,7 +489,8 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>
+	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_kitties::CheckKittyRateLimit<Runtime>,
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
@@ -480,6 +614,28 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_kitties_runtime_api::KittiesApi<Block, u32, Balance, AccountId, BlockNumber> for Runtime {
+		fn remaining_gen0_supply() -> u32 {
+			KittiesModule::remaining_gen0_supply()
+		}
+
+		fn remaining_supply() -> u32 {
+			KittiesModule::remaining_supply()
+		}
+
+		fn floor_price() -> Option<Balance> {
+			KittiesModule::floor_price()
+		}
+
+		fn last_sale(kitty_id: u32) -> Option<pallet_kitties::SaleRecord<AccountId, Balance, BlockNumber>> {
+			KittiesModule::last_sale(kitty_id)
+		}
+
+		fn dna(kitty_id: u32) -> Option<[u8; 16]> {
+			<KittiesModule as pallet_kitties::traits::KittyInspect<AccountId, u32>>::dna_of(kitty_id)
+		}
+	}
+
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
 		fn query_info(
 			uxt: <Block as BlockT>::Extrinsic,
@@ -495,6 +651,15 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[cfg(feature = "try-runtime")]
+	impl frame_try_runtime::TryRuntime<Block> for Runtime {
+		fn on_runtime_upgrade() -> Result<(Weight, Weight), sp_runtime::RuntimeString> {
+			// The post_upgrade hooks, e.g. the storage checks of pallet_kitties, run after the migrations.
+			let weight = Executive::try_runtime_upgrade()?;
+			Ok((weight, BlockWeights::get().max_block))
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(